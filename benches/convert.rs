@@ -0,0 +1,31 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use manchu_converter::{Converter, ManchuConverter};
+
+const PASSAGE: &str =
+    "julgei forgon de emu gašan de emu bayan niyalma bihebi. gebu be baldu bayan sembi.
+tere niyalma i boo banjirengge umesi bayan, aha nehu morin ulha ambula bihebi.
+dulin se de emu haha jui ujifi, tofohon se de isinafi, emu inenggi booi aha be gaifi
+heng lang šan alin de abalame genefi, jugūn de nimeku bahafi bucehebi.
+cooha be acaha manggi, wesimburengge, takūrafi unggihe bithe be tuwaci.
+";
+
+fn corpus() -> String {
+    PASSAGE.repeat(1000)
+}
+
+fn convert(c: &mut Criterion) {
+    let corpus = corpus();
+    let converter = Converter::new();
+    let mut group = c.benchmark_group("convert");
+    group.throughput(Throughput::Bytes(corpus.len() as u64));
+    group.bench_function("converter", |b| {
+        b.iter(|| converter.convert(black_box(&corpus)).unwrap())
+    });
+    group.bench_function("convert_to_manchu", |b| {
+        b.iter(|| black_box(corpus.as_str()).convert_to_manchu(&None).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, convert);
+criterion_main!(benches);
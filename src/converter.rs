@@ -1,13 +1,38 @@
-use std::collections::HashMap;
-
 use unicode_segmentation::UnicodeSegmentation;
 
-use crate::latin_manchu_unicode_mapper::get_latin_manchu_map;
+use crate::detect::{detect_lines, CANDIDATES};
+use crate::error::{ConversionError, Locator};
+use crate::latin_manchu_unicode_mapper::{
+    get_apostrophe_map, get_ascii_punctuation_map, get_pinyin_manchu_map, get_pinyin_tone_marks,
+    get_scheme_table, LatinManchuTable,
+};
+use crate::options::{
+    AsciiProfile, ErrorPolicy, Normalization, ProfileConflict, PunctuationPolicy, Scheme, Target,
+    WhitespacePolicy,
+};
+use crate::token::{TokenKind, Tokens};
+
+/// Result of a conversion that keeps going past errors
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Conversion {
+    /// Converted text, with the words that failed left as they are
+    pub output: String,
+    /// Every error found, in input order
+    pub errors: Vec<ConversionError>,
+    /// Units of the ASCII profiles that could also be read as other letters, in input order
+    pub warnings: Vec<ConversionError>,
+}
+
+impl Conversion {
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
 
 pub trait ManchuConverter {
     /// Convert transcripted texts to Manchu Script and return a String
     ///
-    /// Default value of ignore_error is false
+    /// Default value of ignore_error is false. If it is true, graphemes that cannot be converted are skipped.
     ///
     /// ## Example
     ///
@@ -19,216 +44,994 @@ pub trait ManchuConverter {
     ///     let result = text.convert_to_manchu(&None).unwrap();
     ///     assert_eq!(result, "ᠮᠠᠨᠵᡠ")
     /// }
-    fn convert_to_manchu(&self, ignore_error: &Option<bool>) -> Result<String, String>;
+    fn convert_to_manchu(&self, ignore_error: &Option<bool>) -> Result<String, ConversionError>;
+
+    /// Convert transcripted texts to Manchu Script and collect every error instead of stopping at the first one
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use manchu_converter::ManchuConverter;
+    ///
+    /// fn main() {
+    ///     let text = "manju Qa\ncooha Ze";
+    ///     let result = text.convert_to_manchu_with_diagnostics();
+    ///     assert_eq!(result.output, "ᠮᠠᠨᠵᡠ Qa\nᠴᠣᠣᡥᠠ Ze");
+    ///     assert_eq!(result.errors.len(), 2);
+    ///     assert_eq!(result.errors[1].span().line, 2);
+    /// }
+    fn convert_to_manchu_with_diagnostics(&self) -> Conversion;
 }
 
 impl ManchuConverter for str {
     #[inline]
-    fn convert_to_manchu(&self, ignore_error: &Option<bool>) -> Result<String, String> {
-        let latin_manchu_map = get_latin_manchu_map();
-        let lines = self.lines();
-        // Insert \n between lines
-        let lines_len = lines.clone().count();
-        let lines_manchu = lines.flat_map(|line| {
-            let words = line.split_whitespace().collect::<Vec<&str>>();
-            let result = match words_to_manchu_unicode(words, &latin_manchu_map, ignore_error) {
-                Ok(value) => value,
-                Err(value) => return value,
+    fn convert_to_manchu(&self, ignore_error: &Option<bool>) -> Result<String, ConversionError> {
+        let error_policy = if ignore_error.unwrap_or(false) {
+            ErrorPolicy::Skip
+        } else {
+            ErrorPolicy::Abort
+        };
+        Converter::builder()
+            .error_policy(error_policy)
+            .build()
+            .convert(self)
+    }
+
+    #[inline]
+    fn convert_to_manchu_with_diagnostics(&self) -> Conversion {
+        Converter::new().convert_with_diagnostics(self)
+    }
+}
+
+pub trait MongolianConverter {
+    /// Convert classical Mongolian transliteration to Mongolian Script and return a String
+    ///
+    /// Works like [`ManchuConverter::convert_to_manchu`] with [`Scheme::Mongolian`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use manchu_converter::MongolianConverter;
+    ///
+    /// fn main() {
+    ///     let text = "mongγol-un bičig";
+    ///     let result = text.convert_to_mongolian(&None).unwrap();
+    ///     assert_eq!(result, "ᠮᠣᠩᠭᠣᠯ\u{202f}ᠤᠨ ᠪᠢᠴᠢᠭ")
+    /// }
+    fn convert_to_mongolian(&self, ignore_error: &Option<bool>) -> Result<String, ConversionError>;
+
+    /// Convert classical Mongolian transliteration to Mongolian Script and collect every error
+    /// instead of stopping at the first one
+    fn convert_to_mongolian_with_diagnostics(&self) -> Conversion;
+}
+
+impl MongolianConverter for str {
+    #[inline]
+    fn convert_to_mongolian(&self, ignore_error: &Option<bool>) -> Result<String, ConversionError> {
+        let error_policy = if ignore_error.unwrap_or(false) {
+            ErrorPolicy::Skip
+        } else {
+            ErrorPolicy::Abort
+        };
+        Converter::builder()
+            .scheme(Scheme::Mongolian)
+            .error_policy(error_policy)
+            .build()
+            .convert(self)
+    }
+
+    #[inline]
+    fn convert_to_mongolian_with_diagnostics(&self) -> Conversion {
+        Converter::builder()
+            .scheme(Scheme::Mongolian)
+            .build()
+            .convert_with_diagnostics(self)
+    }
+}
+
+/// Reusable converter holding its options and the prepared mapping table
+///
+/// With [`Scheme::Auto`] the table of every scheme is prepared and each line of a text is read
+/// with the scheme detected in it. A line without any telltale unit is read like the line before,
+/// and in Möllendorff if it is the first.
+///
+/// ## Example
+///
+/// ```rust
+/// use manchu_converter::{Converter, PunctuationPolicy};
+///
+/// fn main() {
+///     let converter = Converter::builder()
+///         .punctuation(PunctuationPolicy::Keep)
+///         .build();
+///     let result = converter.convert("manju, nikan.").unwrap();
+///     assert_eq!(result, "ᠮᠠᠨᠵᡠ, ᠨᡳᡴᠠᠨ.")
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Converter {
+    scheme: Scheme,
+    target: Target,
+    error_policy: ErrorPolicy,
+    whitespace: WhitespacePolicy,
+    punctuation: PunctuationPolicy,
+    normalization: Normalization,
+    ascii_profiles: Option<Vec<AsciiProfile>>,
+    /// Prepared table of each scheme the converter can use
+    tables: Vec<(Scheme, LatinManchuTable)>,
+    /// Prepared table of Hanyu Pinyin for loanwords
+    loanword_table: LatinManchuTable,
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        ConverterBuilder::default().build()
+    }
+}
+
+impl Converter {
+    /// Converter with the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn builder() -> ConverterBuilder {
+        ConverterBuilder::default()
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    pub fn target(&self) -> Target {
+        self.target
+    }
+
+    pub fn error_policy(&self) -> &ErrorPolicy {
+        &self.error_policy
+    }
+
+    pub fn whitespace(&self) -> WhitespacePolicy {
+        self.whitespace
+    }
+
+    pub fn punctuation(&self) -> PunctuationPolicy {
+        self.punctuation
+    }
+
+    pub fn normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    /// The chosen ASCII profiles, or `None` if each scheme uses its default ones
+    pub fn ascii_profiles(&self) -> Option<&[AsciiProfile]> {
+        self.ascii_profiles.as_deref()
+    }
+
+    /// Units of the active ASCII profiles that the schemes read as other letters without the profiles
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use manchu_converter::{AsciiProfile, Converter};
+    ///
+    /// fn main() {
+    ///     let converter = Converter::builder()
+    ///         .ascii_profiles([AsciiProfile::Sh])
+    ///         .build();
+    ///     let conflicts = converter.profile_conflicts();
+    ///     assert_eq!(conflicts[0].unit, "sh");
+    ///     assert_eq!(conflicts[0].native, "ᠰᡥ");
+    /// }
+    /// ```
+    pub fn profile_conflicts(&self) -> Vec<ProfileConflict> {
+        self.tables
+            .iter()
+            .flat_map(|(scheme, table)| {
+                table
+                    .conflicts()
+                    .iter()
+                    .map(|(unit, native)| ProfileConflict {
+                        scheme: *scheme,
+                        unit,
+                        native: native.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Table of Hanyu Pinyin for loanwords
+    pub(crate) fn loanword_table(&self) -> &LatinManchuTable {
+        &self.loanword_table
+    }
+
+    /// Scheme and table of each line of `text`, detected among the prepared tables for
+    /// [`Scheme::Auto`]
+    ///
+    /// Lines without any telltale unit take the scheme of the line before, which is `previous` or
+    /// else Möllendorff for the first line. `in_loanword` is the loanword markup the text starts in.
+    /// Detection is costly, so each conversion calls this once and passes the tables on.
+    pub(crate) fn tables(
+        &self,
+        text: &str,
+        previous: Option<Scheme>,
+        in_loanword: bool,
+    ) -> LineTables<'_> {
+        if self.scheme != Scheme::Auto {
+            let (scheme, table) = &self.tables[0];
+            return LineTables {
+                lines: vec![(0, *scheme, table)],
             };
-            Ok(result)
-        });
-        let mut convert_result = String::new();
-        lines_manchu.enumerate().for_each(|(i, line)| {
-            convert_result.push_str(&line);
-            if i != lines_len - 1 {
-                convert_result.push_str("\n");
+        }
+        let detected = detect_lines(text, &self.tables, in_loanword);
+        let mut previous = previous.unwrap_or(Scheme::Mollendorff);
+        let mut lines = Vec::new();
+        for (start, detection) in detected {
+            let scheme = detection.map_or(previous, |detection| detection.scheme);
+            if lines.is_empty() || scheme != previous {
+                lines.push((start, scheme, self.scheme_table(scheme)));
             }
-        });
+            previous = scheme;
+        }
+        if lines.is_empty() {
+            lines.push((0, previous, self.scheme_table(previous)));
+        }
+        LineTables { lines }
+    }
+
+    /// Prepared table of `scheme`
+    fn scheme_table(&self, scheme: Scheme) -> &LatinManchuTable {
+        self.tables
+            .iter()
+            .find(|(candidate, _)| *candidate == scheme)
+            .map_or(&self.tables[0].1, |(_, table)| table)
+    }
+
+    /// Split transcripted texts into tokens aligned with the converted text
+    pub fn tokens<'a>(&'a self, text: &'a str) -> Tokens<'a> {
+        Tokens::new(self, text, self.target, self.tables(text, None, false))
+    }
 
+    /// Convert transcripted texts to Manchu Script
+    ///
+    /// Text between "{" and "}" is read as Hanyu Pinyin like [`Converter::convert_pinyin`]. A "}"
+    /// outside a loanword, a "{" inside one and a "{" that is never closed are errors.
+    /// Returns the first error if the error policy is [`ErrorPolicy::Abort`], otherwise errors are recovered from.
+    pub fn convert(&self, text: &str) -> Result<String, ConversionError> {
+        let mut convert_result = String::with_capacity(text.len() * 3);
+        self.convert_into(text, &mut convert_result)?;
         Ok(convert_result)
     }
-}
 
-fn words_to_manchu_unicode(
-    words: Vec<&str>,
-    latin_manchu_map: &HashMap<&str, u16>,
-    ignore_error: &Option<bool>,
-) -> Result<String, Result<String, String>> {
-    let mut convert_result = String::new();
-    let mut has_error = false;
-    let mut error_words = Vec::new();
-    for word in words {
-        match convert_latin_to_manchu_unicode(word, latin_manchu_map, ignore_error) {
-            Ok(unicode_list) => {
-                let text = String::from_utf16(unicode_list.as_slice()).unwrap();
-                convert_result.push_str(&text);
+    /// Convert transcripted texts to Manchu Script and append the result to `output`
+    ///
+    /// Reusing `output` across calls avoids allocating for every text.
+    /// On error, `output` is left as it was.
+    pub fn convert_into(&self, text: &str, output: &mut String) -> Result<(), ConversionError> {
+        let start = output.len();
+        let mut errors = Vec::new();
+        let mut loanword = Loanword::default();
+        let tables = self.tables(text, None, loanword.open);
+        self.convert_lines(text, &tables, &mut loanword, &mut errors, output);
+        loanword.finish(&mut errors);
+        match errors.into_iter().next() {
+            Some(error) if self.error_policy == ErrorPolicy::Abort => {
+                output.truncate(start);
+                Err(error)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Convert Chinese loanwords written in Hanyu Pinyin to Manchu Script
+    ///
+    /// The whole text is read as pinyin, as if it were between "{" and "}". Syllables are spelled
+    /// the way Manchu writes Chinese, with the foreign-word letters for zhi, chi, ri, zi, ci and si.
+    /// Tone marks are left out.
+    /// Returns the first error if the error policy is [`ErrorPolicy::Abort`], otherwise errors are recovered from.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use manchu_converter::Converter;
+    ///
+    /// fn main() {
+    ///     let converter = Converter::new();
+    ///     assert_eq!(converter.convert_pinyin("Zhongshi").unwrap(), "ᠵᡠᠩᡧᡳ");
+    ///     assert_eq!(converter.convert_pinyin("zi ci si").unwrap(), "ᡯᡟ ᡮᡟ ᠰᡟ");
+    ///     assert_eq!(
+    ///         converter.convert_pinyin("zi").unwrap(),
+    ///         converter.convert("{zi}").unwrap()
+    ///     );
+    /// }
+    /// ```
+    pub fn convert_pinyin(&self, text: &str) -> Result<String, ConversionError> {
+        let mut errors = Vec::new();
+        let mut output = String::with_capacity(text.len() * 3);
+        let mut loanword = Loanword::pinyin();
+        let tables = self.tables(text, None, loanword.open);
+        self.convert_lines(text, &tables, &mut loanword, &mut errors, &mut output);
+        match errors.into_iter().next() {
+            Some(error) if self.error_policy == ErrorPolicy::Abort => Err(error),
+            _ => Ok(output),
+        }
+    }
+
+    /// Convert transcripted texts to Manchu Script and collect every error
+    ///
+    /// With [`ErrorPolicy::Abort`] the words that failed are left as they are,
+    /// otherwise they are converted according to the error policy.
+    /// Every unit of the active ASCII profiles that could also be read as other letters, such as
+    /// "sh" in "ashan", is warned about with [`ConversionError::AmbiguousUnit`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use manchu_converter::{AsciiProfile, Converter};
+    ///
+    /// fn main() {
+    ///     let converter = Converter::builder()
+    ///         .ascii_profiles([AsciiProfile::Sh, AsciiProfile::DoubleU])
+    ///         .build();
+    ///     let result = converter.convert_with_diagnostics("shun ashan");
+    ///     assert_eq!(result.output, "ᡧᡠᠨ ᠠᡧᠠᠨ");
+    ///     assert_eq!(result.warnings.len(), 2);
+    ///     assert_eq!(result.warnings[1].word(), "ashan");
+    /// }
+    /// ```
+    pub fn convert_with_diagnostics(&self, text: &str) -> Conversion {
+        let mut errors = Vec::new();
+        let mut output = String::with_capacity(text.len() * 3);
+        let mut loanword = Loanword::default();
+        let tables = self.tables(text, None, loanword.open);
+        self.convert_lines(text, &tables, &mut loanword, &mut errors, &mut output);
+        loanword.finish(&mut errors);
+        Conversion {
+            output,
+            errors,
+            warnings: self.ambiguity_warnings(text, tables),
+        }
+    }
+
+    /// Warn about every unit of the active ASCII profiles that could also be read as other letters
+    fn ambiguity_warnings(&self, text: &str, tables: LineTables) -> Vec<ConversionError> {
+        if tables
+            .lines
+            .iter()
+            .all(|(_, _, table)| table.conflicts().is_empty())
+        {
+            return Vec::new();
+        }
+        let mut locator = Locator::new(text);
+        Tokens::new(self, text, self.target, tables.clone())
+            .filter(|token| token.kind == TokenKind::Letter)
+            .filter_map(|token| {
+                let (_, table) = tables.at(token.input.start);
+                let native = table.conflict(token.unit)?;
+                Some(ConversionError::AmbiguousUnit {
+                    word: word_around(text, token.input.start, token.input.end).to_string(),
+                    grapheme: token.unit.to_string(),
+                    native: native.to_string(),
+                    span: locator.locate(token.input.start, token.input.end),
+                })
+            })
+            .collect()
+    }
+
+    /// Convert `text` with the table of each line, starting and ending in the loanword markup of
+    /// `loanword`
+    ///
+    /// Only the lines in schemes that write Manchu Script read loanword markup.
+    pub(crate) fn convert_lines(
+        &self,
+        text: &str,
+        tables: &LineTables,
+        loanword: &mut Loanword,
+        errors: &mut Vec<ConversionError>,
+        convert_result: &mut String,
+    ) {
+        let start = convert_result.len();
+        let (scheme, table) = tables.at(0);
+        let mut reader = Reader {
+            tables,
+            table,
+            loanword_table: &self.loanword_table,
+            scheme,
+            target: self.target,
+            in_loanword: loanword.open,
+            opening: None,
+            locator: Locator::new(text),
+        };
+        let reader = &mut reader;
+        match self.whitespace {
+            WhitespacePolicy::Collapse => {
+                lines_to_manchu_unicode(text, reader, &self.error_policy, errors, convert_result)
             }
-            Err(_) => {
-                has_error = true;
-                error_words.push(word);
-                convert_result.push_str(word);
+            WhitespacePolicy::Preserve => {
+                layout_to_manchu_unicode(text, reader, &self.error_policy, errors, convert_result)
             }
         }
-        convert_result.push_str(" ");
+        if self.target != Target::Script {
+            let script = convert_result.split_off(start);
+            self.target.render(&script, convert_result);
+        }
+        self.normalization.apply(convert_result, start);
+        if !reader.in_loanword {
+            loanword.unclosed = None;
+        } else if let Some(opening) = reader.opening {
+            loanword.unclosed = Some(ConversionError::UnbalancedMarkup {
+                word: word_around(text, opening, opening + 1).to_string(),
+                grapheme: LOANWORD_START.to_string(),
+                span: reader.locator.locate(opening, opening + 1),
+            });
+        }
+        loanword.open = reader.in_loanword;
     }
-    if has_error && !ignore_error.unwrap_or(false) {
-        let error_message = format!("Error: Valid syllable not found in {:?}", error_words);
-        return Err(Err(error_message));
+}
+
+/// Word of `text` around the bytes `start..end`
+fn word_around(text: &str, start: usize, end: usize) -> &str {
+    let start = text[..start].rfind(char::is_whitespace).map_or(0, |i| {
+        i + text[i..].chars().next().map_or(0, char::len_utf8)
+    });
+    let end = text[end..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |i| end + i);
+    &text[start..end]
+}
+
+/// Builder for [`Converter`]
+#[derive(Debug, Clone, Default)]
+pub struct ConverterBuilder {
+    scheme: Scheme,
+    target: Target,
+    error_policy: ErrorPolicy,
+    whitespace: WhitespacePolicy,
+    punctuation: PunctuationPolicy,
+    normalization: Normalization,
+    ascii_profiles: Option<Vec<AsciiProfile>>,
+}
+
+impl ConverterBuilder {
+    pub fn scheme(mut self, scheme: Scheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    pub fn target(mut self, target: Target) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.error_policy = error_policy;
+        self
+    }
+
+    pub fn whitespace(mut self, whitespace: WhitespacePolicy) -> Self {
+        self.whitespace = whitespace;
+        self
+    }
+
+    pub fn punctuation(mut self, punctuation: PunctuationPolicy) -> Self {
+        self.punctuation = punctuation;
+        self
+    }
+
+    pub fn normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Accept the units of `ascii_profiles` instead of the default ASCII profiles of the scheme
+    ///
+    /// No profile at all only accepts the units of the scheme itself.
+    /// The profiles only apply to the schemes that write Manchu Script, not to classical Mongolian.
+    pub fn ascii_profiles(
+        mut self,
+        ascii_profiles: impl IntoIterator<Item = AsciiProfile>,
+    ) -> Self {
+        self.ascii_profiles = Some(ascii_profiles.into_iter().collect());
+        self
+    }
+
+    /// Prepare the mapping table and build the converter
+    pub fn build(self) -> Converter {
+        let schemes = match self.scheme {
+            Scheme::Auto => CANDIDATES.to_vec(),
+            scheme => vec![scheme],
+        };
+        let tables = schemes
+            .into_iter()
+            .filter_map(|scheme| {
+                let profiles = match &self.ascii_profiles {
+                    // The profiles spell letters of Manchu Script
+                    _ if !scheme.writes_manchu() => &[],
+                    Some(profiles) => profiles.as_slice(),
+                    None => scheme.default_ascii_profiles(),
+                };
+                Some((scheme, read_table(scheme, profiles, self.punctuation)?))
+            })
+            .collect();
+        let mut loanword_table = LatinManchuTable::new(get_pinyin_manchu_map());
+        loanword_table.ignore(get_pinyin_tone_marks());
+        if self.punctuation == PunctuationPolicy::Keep {
+            loanword_table.extend(get_ascii_punctuation_map());
+        }
+        Converter {
+            scheme: self.scheme,
+            target: self.target,
+            error_policy: self.error_policy,
+            whitespace: self.whitespace,
+            punctuation: self.punctuation,
+            normalization: self.normalization,
+            ascii_profiles: self.ascii_profiles,
+            tables,
+            loanword_table,
+        }
     }
-    convert_result.pop();
-    Ok(convert_result)
 }
 
-fn convert_latin_to_manchu_unicode(
-    word: &str,
-    latin_manchu_map: &HashMap<&str, u16>,
-    igore_error: &Option<bool>,
-) -> Result<Vec<u16>, String> {
-    let graphemes = UnicodeSegmentation::graphemes(word, true).collect::<Vec<&str>>();
-    let mut unicode_list = Vec::new();
-    let mut i = 0;
-    let mut has_error = false;
-    loop {
-        if i == graphemes.len() {
-            break;
-        }
-        if graphemes[i] == "n" && i != graphemes.len() - 1 {
-            if graphemes[i + 1] == "g" {
-                match latin_manchu_map.get("ng") {
-                    Some(unicode) => {
-                        unicode_list.push(unicode.clone());
-                        i += 2;
-                        continue;
-                    }
-                    None => {
-                        has_error = true;
-                        break;
-                    }
-                }
-            }
+/// Table that `scheme` is read with, extended with `profiles` and with the ASCII punctuation
+/// that `punctuation` copies, or `None` for [`Scheme::Auto`]
+pub(crate) fn read_table(
+    scheme: Scheme,
+    profiles: &[AsciiProfile],
+    punctuation: PunctuationPolicy,
+) -> Option<LatinManchuTable> {
+    let mut table = get_scheme_table(scheme, profiles)?;
+    if punctuation == PunctuationPolicy::Keep {
+        table.extend(get_ascii_punctuation_map());
+        if profiles.contains(&AsciiProfile::DoubleApostrophe) {
+            table.extend(get_apostrophe_map());
         }
-        if graphemes[i] == "t" && i != graphemes.len() - 1 {
-            if graphemes[i + 1] == "s" && graphemes[i + 2] == "'" {
-                match latin_manchu_map.get("ts'") {
-                    Some(unicode) => {
-                        unicode_list.push(unicode.clone());
-                        i += 3;
-                        continue;
-                    }
-                    None => {
-                        has_error = true;
-                        break;
-                    }
-                }
-            }
+    }
+    Some(table)
+}
+
+/// Opens a Chinese loanword written in Hanyu Pinyin
+pub(crate) const LOANWORD_START: char = '{';
+
+/// Closes a Chinese loanword written in Hanyu Pinyin
+pub(crate) const LOANWORD_END: char = '}';
+
+/// Whether `text` starts with loanword markup, and if so whether the markup opens a loanword
+pub(crate) fn markup(text: &str) -> Option<bool> {
+    match text.chars().next()? {
+        LOANWORD_START => Some(true),
+        LOANWORD_END => Some(false),
+        _ => None,
+    }
+}
+
+/// Loanword markup that a part of the input starts and ends in
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Loanword {
+    /// Whether the position is between "{" and "}"
+    pub(crate) open: bool,
+    /// Error for the "{" of the open loanword, reported if it is never closed
+    pub(crate) unclosed: Option<ConversionError>,
+}
+
+impl Loanword {
+    /// Markup of [`Converter::convert_pinyin`], which reads the whole text as a loanword
+    fn pinyin() -> Self {
+        Loanword {
+            open: true,
+            unclosed: None,
         }
-        if graphemes[i] == "d" && i != graphemes.len() - 1 {
-            if graphemes[i + 1] == "z" {
-                match latin_manchu_map.get("dz") {
-                    Some(unicode) => {
-                        unicode_list.push(unicode.clone());
-                        i += 2;
-                        continue;
-                    }
-                    None => {
-                        has_error = true;
-                        break;
-                    }
-                }
-            }
+    }
+
+    /// Report a "{" still open at the end of the input among `errors`, in input order
+    pub(crate) fn finish(self, errors: &mut Vec<ConversionError>) {
+        if let Some(error) = self.unclosed {
+            let start = error.span().start;
+            let i = errors.partition_point(|other| other.span().start <= start);
+            errors.insert(i, error);
         }
-        if graphemes[i] == "k" && i != graphemes.len() - 1 {
-            if graphemes[i + 1] == "'" {
-                match latin_manchu_map.get("k'") {
-                    Some(unicode) => {
-                        unicode_list.push(unicode.clone());
-                        i += 2;
-                        continue;
-                    }
-                    None => {
-                        has_error = true;
-                        break;
-                    }
-                }
-            }
+    }
+}
+
+/// Scheme and table of each line of a text, in input order
+#[derive(Debug, Clone)]
+pub(crate) struct LineTables<'a> {
+    /// Byte offset where each run of lines in the same scheme starts, with the scheme and its table
+    lines: Vec<(usize, Scheme, &'a LatinManchuTable)>,
+}
+
+impl<'a> LineTables<'a> {
+    /// Scheme and table of the line at byte `offset`
+    pub(crate) fn at(&self, offset: usize) -> (Scheme, &'a LatinManchuTable) {
+        let i = self.lines.partition_point(|(start, _, _)| *start <= offset);
+        let (_, scheme, table) = self.lines[i.saturating_sub(1)];
+        (scheme, table)
+    }
+
+    /// Scheme of the last line, which the lines after it without any telltale unit keep
+    pub(crate) fn last(&self) -> Scheme {
+        self.lines
+            .last()
+            .map_or(Scheme::Mollendorff, |(_, scheme, _)| *scheme)
+    }
+}
+
+/// The tables a text is read with, and whether the position is in a loanword
+struct Reader<'a> {
+    tables: &'a LineTables<'a>,
+    /// Table of the line of the word being read
+    table: &'a LatinManchuTable,
+    loanword_table: &'a LatinManchuTable,
+    /// Scheme of the line of the word being read
+    scheme: Scheme,
+    target: Target,
+    in_loanword: bool,
+    /// Byte offset of the "{" of the open loanword, if it is in the text
+    opening: Option<usize>,
+    /// Locates the errors in the text
+    locator: Locator<'a>,
+}
+
+impl<'a> Reader<'a> {
+    fn table(&self) -> &'a LatinManchuTable {
+        if self.in_loanword {
+            self.loanword_table
+        } else {
+            self.table
         }
-        if graphemes[i] == "g" && i != graphemes.len() - 1 {
-            if graphemes[i + 1] == "'" {
-                match latin_manchu_map.get("g'") {
-                    Some(unicode) => {
-                        unicode_list.push(unicode.clone());
-                        i += 2;
-                        continue;
-                    }
-                    None => {
-                        has_error = true;
-                        break;
-                    }
-                }
+    }
+
+    /// Read the line at byte `offset` with its scheme
+    fn enter(&mut self, offset: usize) {
+        let (scheme, table) = self.tables.at(offset);
+        self.table = table;
+        self.scheme = scheme;
+    }
+
+    /// Whether `text` starts with loanword markup that the scheme reads, like [`markup`]
+    fn markup(&self, text: &str) -> Option<bool> {
+        markup(text).filter(|_| self.scheme.writes_manchu())
+    }
+
+    /// Whether the target writes the letters of the scheme, which only Manchu Script does for
+    /// classical Mongolian
+    fn writes_scheme(&self) -> bool {
+        self.target == Target::Script || self.scheme.writes_manchu()
+    }
+
+    /// Open or close a loanword with the markup at `offset`
+    fn follow(&mut self, open: bool, offset: usize) {
+        self.in_loanword = open;
+        self.opening = open.then_some(offset);
+    }
+
+    /// Follow the loanword markup in a part of the text at `offset` that is not converted
+    fn skip(&mut self, text: &str, offset: usize) {
+        for (i, _) in text.char_indices() {
+            match self.markup(&text[i..]) {
+                Some(open) if open != self.in_loanword => self.follow(open, offset + i),
+                _ => {}
             }
         }
-        if graphemes[i] == "h" && i != graphemes.len() - 1 {
-            if graphemes[i + 1] == "'" {
-                match latin_manchu_map.get("h'") {
-                    Some(unicode) => {
-                        unicode_list.push(unicode.clone());
-                        i += 2;
-                        continue;
-                    }
-                    None => {
-                        has_error = true;
-                        break;
-                    }
-                }
-            }
+    }
+}
+
+fn lines_to_manchu_unicode(
+    text: &str,
+    reader: &mut Reader,
+    error_policy: &ErrorPolicy,
+    errors: &mut Vec<ConversionError>,
+    convert_result: &mut String,
+) {
+    // Insert \n between lines
+    for (i, line) in text.lines().enumerate() {
+        if i != 0 {
+            convert_result.push('\n');
         }
-        if graphemes[i] == "c" && i != graphemes.len() - 1 {
-            if graphemes[i + 1] == "'" && i != graphemes.len() - 2 {
-                if graphemes[i + 2] == "y" {
-                    match latin_manchu_map.get("c'y") {
-                        Some(unicode) => {
-                            unicode_list.push(unicode.clone());
-                            i += 3;
-                            continue;
-                        }
-                        None => {
-                            has_error = true;
-                            break;
-                        }
-                    }
-                }
+        words_to_manchu_unicode(text, line, reader, error_policy, errors, convert_result);
+    }
+}
+
+/// Byte offset of `part`, a subslice of `text`, from the start of `text`
+fn offset_in(text: &str, part: &str) -> usize {
+    part.as_ptr() as usize - text.as_ptr() as usize
+}
+
+/// What is wrong with a grapheme that could not be converted
+#[derive(Debug)]
+enum WordErrorKind {
+    /// It has no entry in the table
+    Unknown,
+    /// It cuts short the longer unit it holds
+    Truncated(String),
+    /// It is loanword markup without its counterpart
+    Markup,
+    /// It is a word of a scheme that the target does not write
+    Target(Scheme, Target),
+}
+
+/// A grapheme that could not be converted, as byte offsets into its word
+#[derive(Debug)]
+struct WordError {
+    start: usize,
+    end: usize,
+    kind: WordErrorKind,
+}
+
+impl WordError {
+    fn into_conversion_error(
+        self,
+        locator: &mut Locator,
+        word_offset: usize,
+        word: &str,
+    ) -> ConversionError {
+        let grapheme = word[self.start..self.end].to_string();
+        let span = locator.locate(word_offset + self.start, word_offset + self.end);
+        match self.kind {
+            WordErrorKind::Unknown => ConversionError::UnknownGrapheme {
+                word: word.to_string(),
+                grapheme,
+                span,
+            },
+            WordErrorKind::Truncated(expected) => ConversionError::TruncatedUnit {
+                word: word.to_string(),
+                grapheme,
+                expected,
+                span,
+            },
+            WordErrorKind::Markup => ConversionError::UnbalancedMarkup {
+                word: word.to_string(),
+                grapheme,
+                span,
+            },
+            WordErrorKind::Target(scheme, target) => ConversionError::TargetMismatch {
+                word: word.to_string(),
+                scheme,
+                target,
+                span,
+            },
+        }
+    }
+}
+
+fn words_to_manchu_unicode(
+    text: &str,
+    line: &str,
+    reader: &mut Reader,
+    error_policy: &ErrorPolicy,
+    errors: &mut Vec<ConversionError>,
+    convert_result: &mut String,
+) {
+    for (i, word) in line.split_whitespace().enumerate() {
+        if i != 0 {
+            convert_result.push(' ');
+        }
+        word_to_manchu_unicode(
+            offset_in(text, word),
+            word,
+            reader,
+            error_policy,
+            errors,
+            convert_result,
+        );
+    }
+}
+
+/// Convert only the words and copy every whitespace character as it is
+fn layout_to_manchu_unicode(
+    text: &str,
+    reader: &mut Reader,
+    error_policy: &ErrorPolicy,
+    errors: &mut Vec<ConversionError>,
+    convert_result: &mut String,
+) {
+    for_each_word(
+        text,
+        char::is_whitespace,
+        convert_result,
+        |offset, word, convert_result| {
+            word_to_manchu_unicode(offset, word, reader, error_policy, errors, convert_result)
+        },
+    );
+}
+
+/// Split `text` into words at every character that `is_separator` holds for, copy the separators
+/// to `convert_result` as they are and call `convert_word` with the offset and text of each word
+pub(crate) fn for_each_word(
+    text: &str,
+    is_separator: impl Fn(char) -> bool,
+    convert_result: &mut String,
+    mut convert_word: impl FnMut(usize, &str, &mut String),
+) {
+    let mut word_start = None;
+    for (i, c) in text.char_indices() {
+        match (is_separator(c), word_start) {
+            (true, Some(start)) => {
+                convert_word(start, &text[start..i], convert_result);
+                word_start = None;
+                convert_result.push(c);
             }
+            (true, None) => convert_result.push(c),
+            (false, None) => word_start = Some(i),
+            (false, Some(_)) => {}
+        }
+    }
+    if let Some(start) = word_start {
+        convert_word(start, &text[start..], convert_result);
+    }
+}
+
+/// Convert a word at `offset` in the text and append it to `convert_result`
+fn word_to_manchu_unicode(
+    offset: usize,
+    word: &str,
+    reader: &mut Reader,
+    error_policy: &ErrorPolicy,
+    errors: &mut Vec<ConversionError>,
+    convert_result: &mut String,
+) {
+    let start = convert_result.len();
+    let mut word_errors = Vec::new();
+    reader.enter(offset);
+    if reader.writes_scheme() {
+        convert_latin_to_manchu_unicode(
+            word,
+            offset,
+            reader,
+            error_policy,
+            &mut word_errors,
+            convert_result,
+        );
+    } else {
+        word_errors.push(WordError {
+            start: 0,
+            end: word.len(),
+            kind: WordErrorKind::Target(reader.scheme, reader.target),
+        });
+        match error_policy {
+            ErrorPolicy::Abort => {}
+            ErrorPolicy::PassThrough => convert_result.push_str(word),
+            ErrorPolicy::Replace(marker) => convert_result.push_str(marker),
+            ErrorPolicy::Skip => {}
         }
-        match latin_manchu_map.get(graphemes[i]) {
-            Some(unicode) => {
-                unicode_list.push(unicode.clone());
+    }
+    if !word_errors.is_empty() && *error_policy == ErrorPolicy::Abort {
+        convert_result.truncate(start);
+        convert_result.push_str(word);
+    }
+    errors.extend(
+        word_errors
+            .into_iter()
+            .map(|error| error.into_conversion_error(&mut reader.locator, offset, word)),
+    );
+}
+
+/// Convert a word at `offset` in the text, reading the parts between "{" and "}" as Hanyu Pinyin
+fn convert_latin_to_manchu_unicode(
+    word: &str,
+    offset: usize,
+    reader: &mut Reader,
+    error_policy: &ErrorPolicy,
+    errors: &mut Vec<WordError>,
+    convert_result: &mut String,
+) {
+    let mut i = 0;
+    // Byte offset where the last converted unit starts
+    let mut unit_start = None;
+    while i < word.len() {
+        let rest = &word[i..];
+        let table = reader.table();
+        // End of the grapheme at `i`, only needed when it is reported
+        let end = || i + rest.graphemes(true).next().map_or(rest.len(), str::len);
+        let error = match reader.markup(rest) {
+            Some(open) if open != reader.in_loanword => {
+                reader.follow(open, offset + i);
+                unit_start = None;
                 i += 1;
                 continue;
             }
-            None => {
-                has_error = true;
+            // A "}" outside a loanword or a "{" inside one
+            Some(_) => {
+                unit_start = None;
+                WordError {
+                    start: i,
+                    end: end(),
+                    kind: WordErrorKind::Markup,
+                }
+            }
+            None => match table.longest_match(rest) {
+                Some((len, unicode)) => {
+                    convert_result.push_str(unicode);
+                    unit_start = Some(i);
+                    i += len;
+                    continue;
+                }
+                None => word_error(word, unit_start.take(), i, end(), table),
+            },
+        };
+        let end = error.end;
+        errors.push(error);
+        match error_policy {
+            ErrorPolicy::Abort => {
+                reader.skip(&word[end..], offset + end);
                 break;
             }
+            ErrorPolicy::PassThrough => convert_result.push_str(&word[i..end]),
+            ErrorPolicy::Replace(marker) => convert_result.push_str(marker),
+            ErrorPolicy::Skip => {}
         }
+        i = end;
     }
-    if has_error && !igore_error.unwrap_or(false) {
-        let error_message = format!("Error: Valid syllable not found in {:?}", word);
-        return Err(error_message);
-    }
-    Ok(unicode_list)
+}
+
+/// Error for the grapheme at `start..end`, which is a truncated unit if it continues the unit at `unit_start`
+fn word_error(
+    word: &str,
+    unit_start: Option<usize>,
+    start: usize,
+    end: usize,
+    table: &LatinManchuTable,
+) -> WordError {
+    truncated_unit(word, unit_start, end, table).unwrap_or(WordError {
+        start,
+        end,
+        kind: WordErrorKind::Unknown,
+    })
+}
+
+/// Check whether the last converted unit and the failing grapheme are the beginning of a longer unit
+fn truncated_unit(
+    word: &str,
+    unit_start: Option<usize>,
+    end: usize,
+    table: &LatinManchuTable,
+) -> Option<WordError> {
+    let start = unit_start?;
+    let partial = &word[start..end];
+    let expected = table.longer_unit(partial)?;
+    Some(WordError {
+        start,
+        end,
+        kind: WordErrorKind::Truncated(expected.to_string()),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::Span;
+    use crate::latin_converter::LatinConverter;
+    use crate::latin_manchu_unicode_mapper::get_latin_manchu_map;
 
     #[test]
     fn it_works() {
-        let latin_manchu_map = get_latin_manchu_map();
-        let result = convert_latin_to_manchu_unicode("takūrafi", &latin_manchu_map, &None).unwrap();
+        let table = LatinManchuTable::new(get_latin_manchu_map());
+        let tables = LineTables {
+            lines: vec![(0, Scheme::Mollendorff, &table)],
+        };
+        let mut reader = Reader {
+            tables: &tables,
+            table: &table,
+            loanword_table: &table,
+            scheme: Scheme::Mollendorff,
+            target: Target::Script,
+            in_loanword: false,
+            opening: None,
+            locator: Locator::new("takūrafi"),
+        };
+        let mut errors = Vec::new();
+        let mut result = String::new();
+        convert_latin_to_manchu_unicode(
+            "takūrafi",
+            0,
+            &mut reader,
+            &ErrorPolicy::Abort,
+            &mut errors,
+            &mut result,
+        );
+        assert!(errors.is_empty());
         assert_eq!(
             result,
-            vec![0x1868, 0x1820, 0x1874, 0x1861, 0x1875, 0x1820, 0x1876, 0x1873]
+            "\u{1868}\u{1820}\u{1874}\u{1861}\u{1875}\u{1820}\u{1876}\u{1873}"
         );
 
         let text = "cooha be acaha";
@@ -243,4 +1046,619 @@ mod tests {
         let r = text.convert_to_manchu(&None).unwrap();
         assert_eq!(r, "ᠴᠣᠣᡥᠠ ᠪᡝ\nᠠᠴᠠᡥᠠ");
     }
+
+    #[test]
+    fn conversion_error() {
+        let text = "cooha be\nacaQa";
+        let error = text.convert_to_manchu(&None).unwrap_err();
+        assert_eq!(
+            error,
+            ConversionError::UnknownGrapheme {
+                word: "acaQa".to_string(),
+                grapheme: "Q".to_string(),
+                span: Span {
+                    line: 2,
+                    column: 4,
+                    start: 12,
+                    end: 13
+                }
+            }
+        );
+
+        let text = "c'a";
+        let error = text.convert_to_manchu(&None).unwrap_err();
+        assert_eq!(
+            error,
+            ConversionError::TruncatedUnit {
+                word: "c'a".to_string(),
+                grapheme: "c'".to_string(),
+                expected: "c'y".to_string(),
+                span: Span {
+                    line: 1,
+                    column: 1,
+                    start: 0,
+                    end: 2
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn diagnostics() {
+        let text = "cooha Qe\nbe\nacaQa aZa";
+        let result = text.convert_to_manchu_with_diagnostics();
+        assert_eq!(result.output, "ᠴᠣᠣᡥᠠ Qe\nᠪᡝ\nacaQa aZa");
+        let locations = result
+            .errors
+            .iter()
+            .map(|error| (error.grapheme(), error.span().line, error.span().column))
+            .collect::<Vec<_>>();
+        assert_eq!(locations, vec![("Q", 1, 7), ("Q", 3, 4), ("Z", 3, 8)]);
+
+        let result = "cooha be".convert_to_manchu_with_diagnostics();
+        assert!(!result.has_errors());
+        assert_eq!(result.output, "ᠴᠣᠣᡥᠠ ᠪᡝ");
+    }
+
+    #[test]
+    fn converter_options() {
+        let converter = Converter::new();
+        assert_eq!(converter.convert("manju, nikan.").unwrap(), "ᠮᠠᠨᠵᡠ᠂ ᠨᡳᡴᠠᠨ᠃");
+        assert!(converter.convert("manju!").is_err());
+
+        let converter = Converter::builder()
+            .punctuation(PunctuationPolicy::Keep)
+            .build();
+        assert_eq!(converter.convert("manju, nikan!").unwrap(), "ᠮᠠᠨᠵᡠ, ᠨᡳᡴᠠᠨ!");
+        assert!(converter.convert("c'a").is_err());
+
+        let converter = Converter::builder()
+            .normalization(Normalization::Nfc)
+            .build();
+        let result = converter.convert_with_diagnostics("cafe\u{301}");
+        assert_eq!(result.output, "caf\u{e9}");
+        assert_eq!(result.errors[0].grapheme(), "e\u{301}");
+    }
+
+    #[test]
+    fn case_and_normalization() {
+        let expected = "ᠮᠠᠨᠵᡠ ᡧᠣᠯᠣ ᡨᠠᡴᡡᡵᠠᡶᡳ";
+        for text in [
+            "Manju \u{160}olo Tak\u{16b}rafi",
+            "MANJU S\u{30c}OLO TAKU\u{304}RAFI",
+            "manju s\u{30c}olo taku\u{304}rafi",
+        ] {
+            assert_eq!(text.convert_to_manchu(&None).unwrap(), expected, "{}", text);
+        }
+        let converter = Converter::builder().scheme(Scheme::Xinmanhan).build();
+        assert_eq!(converter.convert("QOOHA").unwrap(), "ᠴᠣᠣᡥᠠ");
+        let error = "C'a".convert_to_manchu(&None).unwrap_err();
+        assert!(
+            matches!(error, ConversionError::TruncatedUnit { expected, .. } if expected == "c'y")
+        );
+    }
+
+    #[test]
+    fn error_policy() {
+        let text = "manjuQabc";
+        let convert = |error_policy| {
+            Converter::builder()
+                .error_policy(error_policy)
+                .build()
+                .convert(text)
+        };
+        assert!(convert(ErrorPolicy::Abort).is_err());
+        assert_eq!(convert(ErrorPolicy::PassThrough).unwrap(), "ᠮᠠᠨᠵᡠQᠠᠪᠴ");
+        assert_eq!(
+            convert(ErrorPolicy::replacement_character()).unwrap(),
+            "ᠮᠠᠨᠵᡠ\u{fffd}ᠠᠪᠴ"
+        );
+        assert_eq!(
+            convert(ErrorPolicy::Replace("[?]".to_string())).unwrap(),
+            "ᠮᠠᠨᠵᡠ[?]ᠠᠪᠴ"
+        );
+        assert_eq!(convert(ErrorPolicy::Skip).unwrap(), "ᠮᠠᠨᠵᡠᠠᠪᠴ");
+        assert_eq!(text.convert_to_manchu(&Some(true)).unwrap(), "ᠮᠠᠨᠵᡠᠠᠪᠴ");
+
+        let result = Converter::builder()
+            .error_policy(ErrorPolicy::Skip)
+            .build()
+            .convert_with_diagnostics("QmanjuZZ");
+        assert_eq!(result.output, "ᠮᠠᠨᠵᡠ");
+        assert_eq!(result.errors.len(), 3);
+    }
+
+    #[test]
+    fn preserve_whitespace() {
+        let converter = Converter::builder()
+            .whitespace(WhitespacePolicy::Preserve)
+            .build();
+        let text = "  cooha\tbe   acaha\r\n\n\tmanju\n";
+        assert_eq!(
+            converter.convert(text).unwrap(),
+            "  ᠴᠣᠣᡥᠠ\tᠪᡝ   ᠠᠴᠠᡥᠠ\r\n\n\tᠮᠠᠨᠵᡠ\n"
+        );
+        assert_eq!(converter.convert("").unwrap(), "");
+        assert_eq!(converter.convert(" \r\n").unwrap(), " \r\n");
+
+        let result = converter.convert_with_diagnostics("cooha\r\n  acaQa ");
+        assert_eq!(result.output, "ᠴᠣᠣᡥᠠ\r\n  acaQa ");
+        assert_eq!(result.errors[0].span().line, 2);
+        assert_eq!(result.errors[0].span().column, 6);
+    }
+
+    #[test]
+    fn no_panic() {
+        let letters = [
+            "t", "s", "'", "c", "y", "n", "g", "d", "z", "k", "Q", " ", "u", "\u{304}",
+        ];
+        let converter = Converter::builder()
+            .error_policy(ErrorPolicy::PassThrough)
+            .build();
+        for a in letters {
+            for b in letters {
+                for c in letters {
+                    let text = [a, b, c].concat();
+                    let _ = text.convert_to_manchu(&None);
+                    let _ = converter.convert_with_diagnostics(&text);
+                    let _ = converter.tokens(&text).count();
+                }
+            }
+        }
+        assert_eq!("ts".convert_to_manchu(&None).unwrap(), "ᡨᠰ");
+        assert_eq!("mats".convert_to_manchu(&None).unwrap(), "ᠮᠠᡨᠰ");
+    }
+
+    #[test]
+    fn abkai() {
+        let converter = Converter::builder().scheme(Scheme::Abkai).build();
+        assert_eq!(converter.convert("wesimburengge").unwrap(), "ᠸᡝᠰᡳᠮᠪᡠᡵᡝᠩᡤᡝ");
+        assert_eq!(
+            converter.convert("takuurafi xolo").unwrap(),
+            "takūrafi šolo".convert_to_manchu(&None).unwrap()
+        );
+        assert_eq!(
+            converter.convert("tsz kkgghh ch").unwrap(),
+            "ts'dz k'g'h' c'y".convert_to_manchu(&None).unwrap()
+        );
+        assert!(converter.convert("takūrafi").is_err());
+    }
+
+    #[test]
+    fn xinmanhan() {
+        let converter = Converter::builder().scheme(Scheme::Xinmanhan).build();
+        assert_eq!(
+            converter.convert("qooha be aqaha").unwrap(),
+            "ᠴᠣᠣᡥᠠ ᠪᡝ ᠠᠴᠠᡥᠠ"
+        );
+        assert_eq!(
+            converter.convert("takvrafi xolo").unwrap(),
+            "takūrafi šolo".convert_to_manchu(&None).unwrap()
+        );
+        assert_eq!(
+            converter.convert("cz kkgghh ch").unwrap(),
+            "ts'dz k'g'h' c'y".convert_to_manchu(&None).unwrap()
+        );
+    }
+
+    #[test]
+    fn xibe() {
+        let converter = Converter::builder().scheme(Scheme::Xibe).build();
+        assert_eq!(
+            converter.convert("chooha be zhuse, ashan").unwrap(),
+            "cooha be juse, ashan".convert_to_manchu(&None).unwrap()
+        );
+        assert_eq!(
+            converter.convert("cz kkgghh chy").unwrap(),
+            "ts'dz k'g'h' c'y".convert_to_manchu(&None).unwrap()
+        );
+        let converter = Converter::builder()
+            .scheme(Scheme::Xibe)
+            .target(Target::Sibe)
+            .build();
+        assert_eq!(
+            converter.convert("xolo zhuse takvrafi").unwrap(),
+            "ᡧᠣᠯᠣ ᡪᡠᠰᡝ ᡨᠠᡣᡡᠷᠠᡫᡞ"
+        );
+    }
+
+    #[test]
+    fn mongolian() {
+        let converter = Converter::builder().scheme(Scheme::Mongolian).build();
+        assert_eq!(
+            converter.convert("Činggis qaγan, kümün-ü").unwrap(),
+            "ᠴᠢᠩᠭᠢᠰ ᠬᠠᠭᠠᠨ\u{1802} ᠬᠦᠮᠦᠨ\u{202f}ᠦ"
+        );
+        assert_eq!(
+            "ǰirγuγan".convert_to_mongolian(&None).unwrap(),
+            "jirgugan".convert_to_mongolian(&None).unwrap()
+        );
+        assert_eq!(
+            "ᠮᠣᠩᠭᠣᠯ\u{202f}ᠤᠨ ᠪᠢᠴᠢᠭ"
+                .convert_to_scheme(Scheme::Mongolian)
+                .unwrap(),
+            "mongγol-un bičiγ"
+        );
+        let result = "manju".convert_to_mongolian_with_diagnostics();
+        assert_eq!(result.output, "ᠮᠠᠨᠵᠤ");
+        assert!(!result.has_errors());
+
+        // Braces are not loanword markup in Mongolian
+        let error = "{zhi}".convert_to_mongolian(&None).unwrap_err();
+        assert!(matches!(error, ConversionError::UnknownGrapheme { .. }));
+        assert_eq!(error.grapheme(), "{");
+        let result = "{zhi}".convert_to_mongolian_with_diagnostics();
+        assert!(!result.output.contains('\u{1872}'));
+
+        // Each line of a bilingual document is read with its own scheme, and a line without any
+        // telltale unit with the scheme of the line before
+        let converter = Converter::builder().scheme(Scheme::Auto).build();
+        assert_eq!(
+            converter.convert("manju gisun\nmongγol kele").unwrap(),
+            "ᠮᠠᠨᠵᡠ ᡤᡳᠰᡠᠨ\nᠮᠣᠩᠭᠣᠯ ᠬᠡᠯᠡ"
+        );
+        let text = "mongγol kele\nbičig\n{zhi} takūrafi\nmanju gisun";
+        assert_eq!(
+            converter.convert(text).unwrap(),
+            "ᠮᠣᠩᠭᠣᠯ ᠬᠡᠯᠡ\nᠪᠢᠴᠢᠭ\nᡲ ᡨᠠᡴᡡᡵᠠᡶᡳ\nᠮᠠᠨᠵᡠ ᡤᡳᠰᡠᠨ"
+        );
+        let tokens = converter.tokens(text).collect::<Vec<_>>();
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|token| token.emitted.as_str())
+                .collect::<String>(),
+            converter.convert(text).unwrap()
+        );
+        // ASCII profiles spell Manchu letters and leave Mongolian lines alone
+        let converter = Converter::builder()
+            .scheme(Scheme::Mongolian)
+            .ascii_profiles([AsciiProfile::Sh])
+            .build();
+        assert_eq!(
+            converter.convert("shira").unwrap(),
+            "shira".convert_to_mongolian(&None).unwrap()
+        );
+        assert!(converter.profile_conflicts().is_empty());
+        let converter = Converter::builder()
+            .scheme(Scheme::Auto)
+            .ascii_profiles([AsciiProfile::X])
+            .build();
+        let error = converter.convert("mongγol xaan").unwrap_err();
+        assert!(matches!(error, ConversionError::UnknownGrapheme { .. }));
+        assert_eq!(error.grapheme(), "x");
+        assert_eq!(converter.convert("xolo").unwrap(), "ᡧᠣᠯᠣ");
+        let converter = Converter::builder()
+            .scheme(Scheme::Mongolian)
+            .ascii_profiles([AsciiProfile::DoubleApostrophe])
+            .build();
+        assert_eq!(converter.convert("k'a").unwrap(), "\u{183a}\u{1820}");
+        let error = converter.convert("k''a").unwrap_err();
+        assert!(matches!(error, ConversionError::UnknownGrapheme { .. }));
+        assert_eq!(error.grapheme(), "'");
+
+        // Only Manchu Script writes classical Mongolian
+        let converter = Converter::builder()
+            .scheme(Scheme::Auto)
+            .target(Target::Ipa)
+            .error_policy(ErrorPolicy::PassThrough)
+            .build();
+        let text = "manju gisun\nmongγol kele";
+        let result = converter.convert_with_diagnostics(text);
+        assert_eq!(result.output, "mantʃu kisun\nmongγol kele");
+        let words = result
+            .errors
+            .iter()
+            .map(|error| (error.word(), error.span().line))
+            .collect::<Vec<_>>();
+        assert_eq!(words, vec![("mongγol", 2), ("kele", 2)]);
+        assert!(matches!(
+            result.errors[0],
+            ConversionError::TargetMismatch {
+                scheme: Scheme::Mongolian,
+                target: Target::Ipa,
+                ..
+            }
+        ));
+        let emitted = converter
+            .tokens(text)
+            .map(|token| token.emitted)
+            .collect::<String>();
+        assert_eq!(emitted, result.output);
+        for target in [Target::Katakana, Target::Hangul, Target::Sibe] {
+            let converter = Converter::builder()
+                .scheme(Scheme::Mongolian)
+                .target(target)
+                .build();
+            let error = converter.convert("mongγol").unwrap_err();
+            assert!(matches!(error, ConversionError::TargetMismatch { .. }));
+        }
+    }
+
+    #[test]
+    fn ascii_profiles() {
+        let expected = "takūrafi šolo k'a c'y".convert_to_manchu(&None).unwrap();
+        let converter = Converter::builder()
+            .ascii_profiles([
+                AsciiProfile::DoubleU,
+                AsciiProfile::Sh,
+                AsciiProfile::DoubleApostrophe,
+            ])
+            .build();
+        assert_eq!(
+            converter.convert("takuurafi sholo k''a c''y").unwrap(),
+            expected
+        );
+        assert!(converter.convert("takvrafi").is_err());
+        assert_eq!(converter.convert("ashan").unwrap(), "ᠠᡧᠠᠨ");
+        assert_eq!(
+            converter.convert("ts''y").unwrap(),
+            "ts'y".convert_to_manchu(&None).unwrap()
+        );
+
+        // A single apostrophe is a quotation mark, not part of a letter
+        let error = converter.convert("k'a").unwrap_err();
+        assert!(matches!(
+            &error,
+            ConversionError::TruncatedUnit { expected, .. } if expected == "k''"
+        ));
+        let error = converter.convert("c'y").unwrap_err();
+        assert_eq!(
+            error,
+            ConversionError::TruncatedUnit {
+                word: "c'y".to_string(),
+                grapheme: "c'".to_string(),
+                expected: "c''y".to_string(),
+                span: Span {
+                    line: 1,
+                    column: 1,
+                    start: 0,
+                    end: 2
+                }
+            }
+        );
+        let quoting = Converter::builder()
+            .ascii_profiles([AsciiProfile::DoubleApostrophe])
+            .punctuation(PunctuationPolicy::Keep)
+            .build();
+        assert_eq!(quoting.convert("'k''a'").unwrap(), "'ᠻᠠ'");
+
+        let conflicts = converter
+            .profile_conflicts()
+            .into_iter()
+            .map(|conflict| (conflict.unit, conflict.native))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            conflicts,
+            vec![("uu", "ᡠᡠ".to_string()), ("sh", "ᠰᡥ".to_string())]
+        );
+
+        let result = converter.convert_with_diagnostics("sholo\n  ASHAN takuu");
+        let warnings = result
+            .warnings
+            .iter()
+            .map(|warning| (warning.word(), warning.grapheme(), warning.span().line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![("sholo", "sh", 1), ("ASHAN", "SH", 2), ("takuu", "uu", 2)]
+        );
+
+        // Möllendorff accepts "v" and "x" unless other profiles are chosen
+        assert_eq!(
+            Converter::new().convert("takvrafi xolo").unwrap(),
+            "ᡨᠠᡴᡡᡵᠠᡶᡳ ᡧᠣᠯᠣ"
+        );
+        let strict = Converter::builder().ascii_profiles([]).build();
+        assert!(strict.convert("xolo").is_err());
+        assert!(strict.profile_conflicts().is_empty());
+        assert!(Converter::new()
+            .convert_with_diagnostics("ashan")
+            .warnings
+            .is_empty());
+
+        // Abkai already reads "uu" as ū
+        let converter = Converter::builder()
+            .scheme(Scheme::Abkai)
+            .ascii_profiles([AsciiProfile::DoubleU, AsciiProfile::V])
+            .build();
+        assert!(converter.profile_conflicts().is_empty());
+        assert_eq!(
+            converter.convert("takvrafi takuurafi").unwrap(),
+            "ᡨᠠᡴᡡᡵᠠᡶᡳ ᡨᠠᡴᡡᡵᠠᡶᡳ"
+        );
+    }
+
+    #[test]
+    fn ipa_target() {
+        let converter = Converter::builder().target(Target::Ipa).build();
+        assert_eq!(
+            converter.convert("cooha be takūrafi, šolo si").unwrap(),
+            "tʃʰɔɔχa pə tʰaqʰʊrafi, ʃɔlɔ ɕi"
+        );
+        let result = converter.convert_with_diagnostics("kaQ\nhehe");
+        assert_eq!(result.output, "kaQ\nxəxə");
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(
+            converter.convert("žy jy ṭaḍa ṣa ḃa").unwrap(),
+            "ʐɨ tsɨ ʈaɖʱa ʂa bʱa"
+        );
+
+        // Every letter of Manchu Script has a reading in each target
+        for target in [Target::Ipa, Target::Katakana, Target::Hangul] {
+            let converter = Converter::builder().target(target).build();
+            for (unit, _) in get_latin_manchu_map().units {
+                let result = converter.convert(unit).unwrap();
+                assert!(
+                    !result
+                        .chars()
+                        .any(|c| ('\u{1800}'..='\u{18af}').contains(&c)),
+                    "{:?} {} {}",
+                    target,
+                    unit,
+                    result
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sibe_target() {
+        let converter = Converter::builder().target(Target::Sibe).build();
+        assert_eq!(
+            converter.convert("manju gisun, inengi").unwrap(),
+            "ᠮᠠᠨᡪᡠ ᡤᡞᠰᡠᠨ\u{1802} ᡞᠨᡝᡢᡞ"
+        );
+        assert_eq!(converter.convert("fulgiyan šufa").unwrap(), "ᡫᡠᠯᡤᡞᠶᠠᠨ ᡧᡠᡫᠠ");
+        assert_eq!(converter.convert("žy jy").unwrap(), "ᡲᡟ ᡲ");
+        // Any scheme can be written in Sibe
+        let converter = Converter::builder()
+            .scheme(Scheme::Abkai)
+            .target(Target::Sibe)
+            .build();
+        assert_eq!(converter.convert("kiru").unwrap(), "ᡣᡞᠷᡠ");
+    }
+
+    #[test]
+    fn loanwords() {
+        let converter = Converter::new();
+        let syllables = [
+            ("zhi", "\u{1872}"),
+            ("chi", "\u{1871}"),
+            ("shi", "\u{1867}\u{1873}"),
+            ("ri", "\u{1877}\u{185f}"),
+            ("zi", "\u{186f}\u{185f}"),
+            ("ci", "\u{186e}\u{185f}"),
+            ("si", "\u{1830}\u{185f}"),
+        ];
+        for (pinyin, manchu) in syllables {
+            assert_eq!(
+                converter.convert_pinyin(pinyin).unwrap(),
+                manchu,
+                "{}",
+                pinyin
+            );
+        }
+        assert_eq!(
+            converter.convert_pinyin("Zhou Xi'an liang guo").unwrap(),
+            "jeo sian liyang go".convert_to_manchu(&None).unwrap()
+        );
+        assert_eq!(
+            converter
+                .convert_pinyin("Yuan jun xuan que yue xue qun xun")
+                .unwrap(),
+            "yuwan jiyūn siowan ciowe yuwe siowe ciyūn siyūn"
+                .convert_to_manchu(&None)
+                .unwrap()
+        );
+        assert_eq!(
+            converter.convert_pinyin("ju yu nü").unwrap(),
+            "jioi ioi nioi".convert_to_manchu(&None).unwrap()
+        );
+
+        // Tone marks are not written, and the syllable er is el
+        assert_eq!(
+            converter.convert("{Zhōngguó} {lǜ} {Xī'ān}").unwrap(),
+            converter.convert("{Zhongguo} {lü} {Xi'an}").unwrap()
+        );
+        assert_eq!(
+            converter.convert_pinyin("Zhōngguó").unwrap(),
+            "junggo".convert_to_manchu(&None).unwrap()
+        );
+        assert_eq!(
+            converter
+                .convert_pinyin("er Erlang keren geri ruo'er Sherong")
+                .unwrap(),
+            "el ellang kežen gežy žoel šežung"
+                .convert_to_manchu(&None)
+                .unwrap()
+        );
+
+        // Markup can span words and lines
+        let text = "hafan {Zhang\nSanfeng} i {zi}bithe";
+        let expected = "ᡥᠠᡶᠠᠨ ᠵᠠᠩ\nᠰᠠᠨᡶᡝᠩ ᡳ ᡯᡟᠪᡳᡨᡥᡝ";
+        assert_eq!(converter.convert(text).unwrap(), expected);
+        let converter = Converter::builder()
+            .whitespace(WhitespacePolicy::Preserve)
+            .build();
+        assert_eq!(converter.convert(text).unwrap(), expected);
+        let emitted = converter
+            .tokens(text)
+            .map(|token| token.emitted)
+            .collect::<String>();
+        assert_eq!(emitted, expected);
+
+        // "q" is only pinyin
+        let error = Converter::new().convert("{qi}qi").unwrap_err();
+        assert_eq!(error.span().start, 4);
+        let result = Converter::new().convert_with_diagnostics("aQ{a} {qi}");
+        assert_eq!(result.output, "aQ{a} ᠴᡳ");
+
+        // Unbalanced markup
+        for (text, start) in [("a{b", 1), ("}", 0), ("{a{b}", 2), ("{zi}}", 4)] {
+            let error = Converter::new().convert(text).unwrap_err();
+            assert!(
+                matches!(error, ConversionError::UnbalancedMarkup { .. }),
+                "{}",
+                text
+            );
+            assert_eq!(error.span().start, start, "{}", text);
+        }
+        let text = "manju }\n{zi ci @";
+        let result = Converter::new().convert_with_diagnostics(text);
+        assert_eq!(result.output, "ᠮᠠᠨᠵᡠ }\nᡯᡟ ᡮᡟ @");
+        let errors = result
+            .errors
+            .iter()
+            .map(|error| (error.grapheme(), error.span().line, error.span().column))
+            .collect::<Vec<_>>();
+        assert_eq!(errors, vec![("}", 1, 7), ("{", 2, 1), ("@", 2, 8)]);
+        let converter = Converter::builder().error_policy(ErrorPolicy::Skip).build();
+        let kinds = converter
+            .tokens("a}b")
+            .map(|token| token.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![TokenKind::Letter, TokenKind::Unknown, TokenKind::Letter]
+        );
+        assert_eq!(converter.convert("a}b").unwrap(), "ᠠᠪ");
+    }
+
+    #[test]
+    fn auto_scheme() {
+        let converter = Converter::builder().scheme(Scheme::Auto).build();
+        let expected = "ᠴᠣᠣᡥᠠ ᠪᡝ ᡨᠠᡴᡡᡵᠠᡶᡳ";
+        for text in [
+            "cooha be takūrafi",
+            "cooha be takvrafi",
+            "cooha be takuurafi",
+            "qooha be takvrafi",
+        ] {
+            assert_eq!(converter.convert(text).unwrap(), expected, "{}", text);
+        }
+        let emitted = converter
+            .tokens("qooha")
+            .map(|token| token.emitted)
+            .collect::<String>();
+        assert_eq!(emitted, "ᠴᠣᠣᡥᠠ");
+
+        // Loanwords do not count in detection
+        let text = "hafan {Zhang Qian} i cooha";
+        assert_eq!(
+            converter.convert(text).unwrap(),
+            Converter::new().convert(text).unwrap()
+        );
+
+        // Detection uses the tables of the converter, here without the "v" and "x" of the
+        // default ASCII profiles of Möllendorff
+        let converter = Converter::builder()
+            .scheme(Scheme::Auto)
+            .ascii_profiles([])
+            .build();
+        assert_eq!(
+            converter.convert("takvrafi xolo").unwrap(),
+            "takūrafi šolo".convert_to_manchu(&None).unwrap()
+        );
+    }
 }
@@ -0,0 +1,274 @@
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::converter::{markup, LOANWORD_END, LOANWORD_START};
+use crate::latin_manchu_unicode_mapper::{get_scheme_table, LatinManchuTable};
+use crate::options::Scheme;
+
+/// Schemes told apart by [`detect_scheme`], in order of preference when the evidence is even
+pub(crate) const CANDIDATES: [Scheme; 7] = [
+    Scheme::Mollendorff,
+    Scheme::MollendorffAscii,
+    Scheme::Abkai,
+    Scheme::Xinmanhan,
+    Scheme::Cyrillic,
+    Scheme::Xibe,
+    Scheme::Mongolian,
+];
+
+/// Number of bytes at the start of the text that are inspected
+const SAMPLE_LEN: usize = 1 << 16;
+
+/// Score taken off a scheme for every grapheme its table cannot convert
+const UNKNOWN_PENALTY: f64 = 2.0;
+
+/// Most likely scheme of a text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Detection {
+    /// Never [`Scheme::Auto`]
+    pub scheme: Scheme,
+    /// Share of the evidence that points to `scheme`, from 0 to 1
+    ///
+    /// Text without any telltale unit gives [`Scheme::Mollendorff`] with the confidence of a guess
+    /// among the candidates.
+    pub confidence: f64,
+}
+
+/// Detect the romanization scheme of transcripted texts
+///
+/// Diacritics point to Möllendorff, "v" and "x" without diacritics to its ASCII variant or to
+/// Xinmanhan, "uu" to Abkai, "q" to Xinmanhan, "zh" and "ch" to Xibe, Cyrillic letters to the
+/// Cyrillic transcription and γ, ö, ü, č and ǰ to classical Mongolian.
+/// A scheme loses score for every grapheme its table cannot convert.
+/// Only the first 64 KiB of the text are inspected.
+///
+/// ## Example
+///
+/// ```rust
+/// use manchu_converter::{detect_scheme, Scheme};
+///
+/// fn main() {
+///     assert_eq!(detect_scheme("takūrafi").scheme, Scheme::Mollendorff);
+///     assert_eq!(detect_scheme("takuurafi").scheme, Scheme::Abkai);
+///     assert_eq!(detect_scheme("qooha").scheme, Scheme::Xinmanhan);
+///     assert_eq!(detect_scheme("qooha").confidence, 1.0);
+/// }
+/// ```
+pub fn detect_scheme(text: &str) -> Detection {
+    static TABLES: OnceLock<Vec<(Scheme, LatinManchuTable)>> = OnceLock::new();
+    let tables = TABLES.get_or_init(|| {
+        CANDIDATES
+            .iter()
+            .filter_map(|scheme| {
+                let table = get_scheme_table(*scheme, scheme.default_ascii_profiles())?;
+                Some((*scheme, table))
+            })
+            .collect()
+    });
+    detect_among(text, tables).unwrap_or(Detection {
+        scheme: Scheme::Mollendorff,
+        confidence: 1.0 / CANDIDATES.len() as f64,
+    })
+}
+
+/// Detect the scheme of `text` among the prepared `tables` of some of [`CANDIDATES`]
+///
+/// Returns `None` if nothing in the text points to any of them.
+pub(crate) fn detect_among(text: &str, tables: &[(Scheme, LatinManchuTable)]) -> Option<Detection> {
+    detect_sample(&without_loanwords(sample(text), &mut false), tables)
+}
+
+/// Detect the scheme of each line of `text` among the prepared `tables`, like [`detect_among`]
+///
+/// Returns the byte offset where each line starts, with what its words point to. Loanwords may
+/// span lines, and the text starts in one if `in_loanword`.
+pub(crate) fn detect_lines(
+    text: &str,
+    tables: &[(Scheme, LatinManchuTable)],
+    mut in_loanword: bool,
+) -> Vec<(usize, Option<Detection>)> {
+    let mut start = 0;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let detection = detect_sample(&without_loanwords(line, &mut in_loanword), tables);
+            let line_start = start;
+            start += line.len();
+            (line_start, detection)
+        })
+        .collect()
+}
+
+/// The inspected start of `text`
+fn sample(text: &str) -> &str {
+    let mut end = text.len().min(SAMPLE_LEN);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Detect the scheme of `text` without its loanwords
+fn detect_sample(text: &str, tables: &[(Scheme, LatinManchuTable)]) -> Option<Detection> {
+    let sample = sample(text);
+    let telltales = telltale_scores(sample);
+    let scores = tables
+        .iter()
+        .map(|(scheme, table)| {
+            let telltale = CANDIDATES
+                .iter()
+                .position(|candidate| candidate == scheme)
+                .map_or(0.0, |i| telltales[i]);
+            (telltale - UNKNOWN_PENALTY * unknown_letters(sample, table) as f64).max(0.0)
+        })
+        .collect::<Vec<_>>();
+
+    let total = scores.iter().sum::<f64>();
+    if total == 0.0 {
+        return None;
+    }
+    let mut best = 0;
+    for (i, score) in scores.iter().enumerate() {
+        if *score > scores[best] {
+            best = i;
+        }
+    }
+    Some(Detection {
+        scheme: tables[best].0,
+        confidence: scores[best] / total,
+    })
+}
+
+/// `text` with a space in place of each Chinese loanword between "{" and "}", which is read as
+/// Hanyu Pinyin whatever the scheme
+///
+/// The text starts in a loanword if `in_loanword`, which is left as the markup at its end.
+fn without_loanwords<'a>(text: &'a str, in_loanword: &mut bool) -> Cow<'a, str> {
+    if !*in_loanword && !text.contains(LOANWORD_START) && !text.contains(LOANWORD_END) {
+        return Cow::Borrowed(text);
+    }
+    let mut sample = String::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        match markup(&text[i..]) {
+            Some(true) => {
+                *in_loanword = true;
+                sample.push(' ');
+            }
+            Some(false) => *in_loanword = false,
+            None if !*in_loanword => sample.push(c),
+            None => {}
+        }
+    }
+    Cow::Owned(sample)
+}
+
+/// Score of each of [`CANDIDATES`] from the units only some schemes use
+fn telltale_scores(text: &str) -> [f64; 7] {
+    const MOLLENDORFF: usize = 0;
+    const MOLLENDORFF_ASCII: usize = 1;
+    const ABKAI: usize = 2;
+    const XINMANHAN: usize = 3;
+    const CYRILLIC: usize = 4;
+    const XIBE: usize = 5;
+    const MONGOLIAN: usize = 6;
+
+    let mut scores = [0.0; 7];
+    let mut add = |schemes: &[usize], score: f64| {
+        for scheme in schemes {
+            scores[*scheme] += score;
+        }
+    };
+    let chars = text
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    for (i, c) in chars.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1).copied();
+        match (previous, *c, next) {
+            (_, '\u{16b}' | '\u{161}', _) | (_, 'u' | 's', Some('\u{304}' | '\u{30c}')) => {
+                add(&[MOLLENDORFF], 3.0)
+            }
+            (_, '\'', _) => add(&[MOLLENDORFF, MOLLENDORFF_ASCII], 1.0),
+            (_, 'v', _) => add(&[MOLLENDORFF_ASCII, XINMANHAN, XIBE], 1.0),
+            (_, 'x', _) => add(&[MOLLENDORFF_ASCII, ABKAI, XINMANHAN, XIBE], 1.0),
+            (_, 'q', _) => add(&[XINMANHAN], 3.0),
+            (_, '\u{400}'..='\u{4ff}', _) => add(&[CYRILLIC], 3.0),
+            (_, '\u{3b3}' | '\u{f6}' | '\u{fc}' | '\u{10d}' | '\u{1f0}', _) => {
+                add(&[MONGOLIAN], 3.0)
+            }
+            // The second "u" must not carry a macron
+            (_, 'u', Some('u')) if chars.get(i + 2) != Some(&'\u{304}') => add(&[ABKAI], 2.0),
+            // "zh" for j and "ch" for c, where the other schemes write "ch" only for c'y
+            (_, 'z' | 'c', Some('h')) => add(&[XIBE], 2.0),
+            // "c" for c, where Xinmanhan writes "q"
+            (_, 'c', next) if !matches!(next, Some('h' | '\'')) => {
+                add(&[MOLLENDORFF, MOLLENDORFF_ASCII, ABKAI], 1.0)
+            }
+            (Some('d'), 'z', _) => add(&[MOLLENDORFF, MOLLENDORFF_ASCII], 1.0),
+            (_, 'z', _) => add(&[ABKAI, XINMANHAN, XIBE], 1.0),
+            (_, 'k' | 'g' | 'h', next) if next == Some(*c) => add(&[ABKAI, XINMANHAN, XIBE], 1.0),
+            _ => {}
+        }
+    }
+    scores
+}
+
+/// Number of letters in `text` that `table` cannot convert
+fn unknown_letters(text: &str, table: &LatinManchuTable) -> usize {
+    let mut unknown = 0;
+    let mut position = 0;
+    while let Some(grapheme) = text[position..].graphemes(true).next() {
+        let len = match table.longest_match(&text[position..]) {
+            Some((len, _)) => len,
+            None => {
+                if grapheme.chars().next().is_some_and(char::is_alphabetic) {
+                    unknown += 1;
+                }
+                grapheme.len()
+            }
+        };
+        position += len;
+    }
+    unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect() {
+        let texts = [
+            ("takūrafi cooha be", Scheme::Mollendorff),
+            ("taku\u{304}rafi", Scheme::Mollendorff),
+            ("\u{160}OLO", Scheme::Mollendorff),
+            ("takvrafi c'y xun", Scheme::MollendorffAscii),
+            ("takuurafi xun zi", Scheme::Abkai),
+            ("takvrafi qooha xun", Scheme::Xinmanhan),
+            ("Qooha", Scheme::Xinmanhan),
+            ("Такӯрафи чооха", Scheme::Cyrillic),
+            ("chooha be takvrafi", Scheme::Xibe),
+            ("Zhuse xolo", Scheme::Xibe),
+            ("mongγol kümün", Scheme::Mongolian),
+        ];
+        for (text, scheme) in texts {
+            assert_eq!(detect_scheme(text).scheme, scheme, "{}", text);
+        }
+
+        let detection = detect_scheme("manju gisun");
+        assert_eq!(detection.scheme, Scheme::Mollendorff);
+        assert_eq!(detection.confidence, 1.0 / 7.0);
+
+        // Only Xinmanhan has a "q"
+        let detection = detect_scheme("qooha");
+        assert_eq!(detection.scheme, Scheme::Xinmanhan);
+        assert_eq!(detection.confidence, 1.0);
+        assert!(detect_scheme("takvrafi").confidence < 1.0);
+
+        // Pinyin in loanword markup is no evidence
+        let detection = detect_scheme("hafan {Zhang Qian} i cooha");
+        assert_eq!(detection.scheme, Scheme::Mollendorff);
+    }
+}
@@ -0,0 +1,359 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::options::{Scheme, Target};
+
+/// Location of a piece of the input text
+///
+/// `line` and `column` are 1-based, and `column` counts characters from the start of the line.
+/// `start` and `end` are byte offsets into the whole input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Locates spans in one text, scanning each part of the text once as long as spans come in input
+/// order
+#[derive(Debug, Clone)]
+pub(crate) struct Locator<'a> {
+    text: &'a str,
+    /// Byte offset, line and column of the start of the last span
+    last: (usize, usize, usize),
+}
+
+impl<'a> Locator<'a> {
+    pub(crate) fn new(text: &'a str) -> Self {
+        Locator {
+            text,
+            last: (0, 1, 1),
+        }
+    }
+
+    pub(crate) fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Build a span for `start..end` in the text
+    ///
+    /// Only the text between the last span and this one is scanned, and the line of this one if
+    /// it is before the last one on another line.
+    pub(crate) fn locate(&mut self, start: usize, end: usize) -> Span {
+        let (mut offset, mut line, mut column) = self.last;
+        if start < offset {
+            let between = &self.text[start..offset];
+            let lines = between.matches('\n').count();
+            if lines == 0 {
+                column -= between.chars().count();
+            } else {
+                line -= lines;
+                let line_start = self.text[..start].rfind('\n').map_or(0, |i| i + 1);
+                column = self.text[line_start..start].chars().count() + 1;
+            }
+            offset = start;
+        }
+        for c in self.text[offset..start].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        self.last = (start, line, column);
+        Span {
+            line,
+            column,
+            start,
+            end,
+        }
+    }
+}
+
+/// Error returned when transcripted text cannot be converted
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConversionError {
+    /// The grapheme has no entry in the mapping table
+    UnknownGrapheme {
+        word: String,
+        grapheme: String,
+        span: Span,
+    },
+    /// The grapheme starts a multi-letter unit such as "c'y" but the unit is cut short
+    TruncatedUnit {
+        word: String,
+        grapheme: String,
+        expected: String,
+        span: Span,
+    },
+    /// The code point of Manchu Script has no Latin spelling, such as a free variation selector
+    UnsupportedCodePoint {
+        word: String,
+        grapheme: String,
+        span: Span,
+    },
+    /// The Latin spelling of the letters reads back as other letters, such as "n" and "g" as "ng"
+    AmbiguousSpelling {
+        word: String,
+        grapheme: String,
+        spelling: String,
+        span: Span,
+    },
+    /// The unit of an ASCII profile could also be read as other letters, such as "sh" as "s" and "h"
+    ///
+    /// Only reported as a warning.
+    AmbiguousUnit {
+        word: String,
+        grapheme: String,
+        native: String,
+        span: Span,
+    },
+    /// The unit stands for a letter that the target scheme has no spelling for
+    Unrepresentable {
+        word: String,
+        grapheme: String,
+        scheme: Scheme,
+        span: Span,
+    },
+    /// Loanword markup without its counterpart, such as a "}" outside a loanword or a "{" that is
+    /// never closed
+    UnbalancedMarkup {
+        word: String,
+        grapheme: String,
+        span: Span,
+    },
+    /// The word is read in a scheme whose letters the output target does not write, such as
+    /// classical Mongolian in IPA
+    TargetMismatch {
+        word: String,
+        scheme: Scheme,
+        target: Target,
+        span: Span,
+    },
+}
+
+impl ConversionError {
+    /// The word containing the offending grapheme
+    pub fn word(&self) -> &str {
+        match self {
+            ConversionError::UnknownGrapheme { word, .. } => word,
+            ConversionError::TruncatedUnit { word, .. } => word,
+            ConversionError::UnsupportedCodePoint { word, .. } => word,
+            ConversionError::AmbiguousSpelling { word, .. } => word,
+            ConversionError::AmbiguousUnit { word, .. } => word,
+            ConversionError::Unrepresentable { word, .. } => word,
+            ConversionError::UnbalancedMarkup { word, .. } => word,
+            ConversionError::TargetMismatch { word, .. } => word,
+        }
+    }
+
+    /// The offending grapheme, or the incomplete unit for [`ConversionError::TruncatedUnit`],
+    /// the letters for [`ConversionError::AmbiguousSpelling`] and the unit for
+    /// [`ConversionError::AmbiguousUnit`] and [`ConversionError::Unrepresentable`], and the whole
+    /// word for [`ConversionError::TargetMismatch`]
+    pub fn grapheme(&self) -> &str {
+        match self {
+            ConversionError::UnknownGrapheme { grapheme, .. } => grapheme,
+            ConversionError::TruncatedUnit { grapheme, .. } => grapheme,
+            ConversionError::UnsupportedCodePoint { grapheme, .. } => grapheme,
+            ConversionError::AmbiguousSpelling { grapheme, .. } => grapheme,
+            ConversionError::AmbiguousUnit { grapheme, .. } => grapheme,
+            ConversionError::Unrepresentable { grapheme, .. } => grapheme,
+            ConversionError::UnbalancedMarkup { grapheme, .. } => grapheme,
+            ConversionError::TargetMismatch { word, .. } => word,
+        }
+    }
+
+    /// Where the offending grapheme is in the input
+    pub fn span(&self) -> Span {
+        match self {
+            ConversionError::UnknownGrapheme { span, .. } => *span,
+            ConversionError::TruncatedUnit { span, .. } => *span,
+            ConversionError::UnsupportedCodePoint { span, .. } => *span,
+            ConversionError::AmbiguousSpelling { span, .. } => *span,
+            ConversionError::AmbiguousUnit { span, .. } => *span,
+            ConversionError::Unrepresentable { span, .. } => *span,
+            ConversionError::UnbalancedMarkup { span, .. } => *span,
+            ConversionError::TargetMismatch { span, .. } => *span,
+        }
+    }
+
+    /// Move the span of an error found in a part of the input that starts `columns` characters
+    /// into a line
+    pub(crate) fn shift(&mut self, bytes: usize, lines: usize, columns: usize) {
+        let span = match self {
+            ConversionError::UnknownGrapheme { span, .. } => span,
+            ConversionError::TruncatedUnit { span, .. } => span,
+            ConversionError::UnsupportedCodePoint { span, .. } => span,
+            ConversionError::AmbiguousSpelling { span, .. } => span,
+            ConversionError::AmbiguousUnit { span, .. } => span,
+            ConversionError::Unrepresentable { span, .. } => span,
+            ConversionError::UnbalancedMarkup { span, .. } => span,
+            ConversionError::TargetMismatch { span, .. } => span,
+        };
+        if span.line == 1 {
+            span.column += columns;
+        }
+        span.line += lines;
+        span.start += bytes;
+        span.end += bytes;
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::UnknownGrapheme {
+                word,
+                grapheme,
+                span,
+            } => write!(
+                f,
+                "unknown grapheme {:?} in {:?} at line {}, column {}",
+                grapheme, word, span.line, span.column
+            ),
+            ConversionError::TruncatedUnit {
+                word,
+                grapheme,
+                expected,
+                span,
+            } => write!(
+                f,
+                "truncated unit {:?} (expected {:?}) in {:?} at line {}, column {}",
+                grapheme, expected, word, span.line, span.column
+            ),
+            ConversionError::UnsupportedCodePoint {
+                word,
+                grapheme,
+                span,
+            } => write!(
+                f,
+                "unsupported code point {:?} in {:?} at line {}, column {}",
+                grapheme, word, span.line, span.column
+            ),
+            ConversionError::AmbiguousSpelling {
+                word,
+                grapheme,
+                spelling,
+                span,
+            } => write!(
+                f,
+                "ambiguous spelling {:?} of {:?} in {:?} at line {}, column {}",
+                spelling, grapheme, word, span.line, span.column
+            ),
+            ConversionError::AmbiguousUnit {
+                word,
+                grapheme,
+                native,
+                span,
+            } => write!(
+                f,
+                "ambiguous unit {:?} (also read as {:?}) in {:?} at line {}, column {}",
+                grapheme, native, word, span.line, span.column
+            ),
+            ConversionError::Unrepresentable {
+                word,
+                grapheme,
+                scheme,
+                span,
+            } => write!(
+                f,
+                "{:?} in {:?} cannot be represented in {:?} at line {}, column {}",
+                grapheme, word, scheme, span.line, span.column
+            ),
+            ConversionError::UnbalancedMarkup {
+                word,
+                grapheme,
+                span,
+            } => write!(
+                f,
+                "unbalanced loanword markup {:?} in {:?} at line {}, column {}",
+                grapheme, word, span.line, span.column
+            ),
+            ConversionError::TargetMismatch {
+                word,
+                scheme,
+                target,
+                span,
+            } => write!(
+                f,
+                "{:?} in {:?} cannot be written in {:?} at line {}, column {}",
+                word, scheme, target, span.line, span.column
+            ),
+        }
+    }
+}
+
+impl Error for ConversionError {}
+
+/// Error returned when converting a stream
+#[derive(Debug)]
+pub enum StreamError {
+    /// Reading or writing failed, or the input is not valid UTF-8
+    Io(io::Error),
+    Conversion(ConversionError),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io(error) => write!(f, "{}", error),
+            StreamError::Conversion(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for StreamError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StreamError::Io(error) => Some(error),
+            StreamError::Conversion(error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for StreamError {
+    fn from(error: io::Error) -> Self {
+        StreamError::Io(error)
+    }
+}
+
+impl From<ConversionError> for StreamError {
+    fn from(error: ConversionError) -> Self {
+        StreamError::Conversion(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate_span() {
+        let text = "cooha be\nacaQa";
+        let mut locator = Locator::new(text);
+        let span = locator.locate(12, 13);
+        assert_eq!(
+            span,
+            Span {
+                line: 2,
+                column: 4,
+                start: 12,
+                end: 13
+            }
+        );
+        assert_eq!(locator.locate(13, 14).column, 5);
+        assert_eq!(locator.locate(6, 8).line, 1);
+        assert_eq!(locator.locate(6, 8).column, 7);
+        assert_eq!(locator.locate(10, 11).column, 2);
+        assert_eq!(locator.locate(9, 10).column, 1);
+
+        let text = "takūraQ";
+        let span = Locator::new(text).locate(7, 8);
+        assert_eq!(span.column, 7);
+    }
+}
@@ -0,0 +1,185 @@
+/// First precomposed Hangul syllable, 가
+const SYLLABLE_BASE: u32 = 0xac00;
+
+/// Index of ㅇ, the silent initial of a syllable that starts with a vowel
+const SILENT_INITIAL: u32 = 11;
+
+/// Index of ㅡ, the medial of a consonant standing on its own
+const NEUTRAL_MEDIAL: u32 = 18;
+
+/// Glide that a consonant adds to the vowel after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Glide {
+    None,
+    Y,
+    W,
+}
+
+/// Index of the initial of a consonant and its glide
+fn initial(letter: char) -> Option<(u32, Glide)> {
+    let initial = match letter {
+        '\u{1864}' | '\u{186c}' | '\u{189a}' => 0,
+        '\u{1828}' => 2,
+        '\u{1869}' | '\u{189f}' | '\u{18a1}' => 3,
+        '\u{182f}' | '\u{1875}' | '\u{18aa}' => 5,
+        '\u{182e}' => 6,
+        '\u{182a}' | '\u{18a8}' => 7,
+        '\u{1830}' | '\u{1867}' | '\u{18a2}' => 9,
+        '\u{1829}' | '\u{189b}' => SILENT_INITIAL,
+        '\u{1836}' => return Some((SILENT_INITIAL, Glide::Y)),
+        '\u{1838}' => return Some((SILENT_INITIAL, Glide::W)),
+        '\u{1835}' | '\u{186f}' | '\u{1877}' | '\u{189d}' | '\u{18a4}' | '\u{18a5}' => 12,
+        '\u{1834}' | '\u{186e}' | '\u{189c}' | '\u{18a3}' => 14,
+        '\u{1874}' | '\u{183b}' => 15,
+        '\u{1868}' | '\u{189e}' | '\u{18a0}' => 16,
+        '\u{1866}' | '\u{1876}' => 17,
+        '\u{1865}' | '\u{186d}' => 18,
+        _ => return None,
+    };
+    Some((initial, Glide::None))
+}
+
+/// Index of the medial of a vowel after a glide
+fn medial(letter: char, glide: Glide) -> Option<u32> {
+    let medial = match (letter, glide) {
+        ('\u{1820}', Glide::None) => 0,
+        ('\u{1820}', Glide::Y) => 2,
+        ('\u{1820}', Glide::W) => 9,
+        ('\u{185d}', Glide::None) => 4,
+        ('\u{185d}', Glide::Y) => 6,
+        ('\u{185d}', Glide::W) => 14,
+        ('\u{1873}', Glide::W) => 16,
+        ('\u{1873}', _) => 20,
+        ('\u{1823}', Glide::Y) => 12,
+        ('\u{1823}', Glide::W) => 14,
+        ('\u{1823}', _) => 8,
+        ('\u{1860}' | '\u{1861}', Glide::Y) => 17,
+        ('\u{1860}' | '\u{1861}', _) => 13,
+        ('\u{185f}', _) => NEUTRAL_MEDIAL,
+        _ => return None,
+    };
+    Some(medial)
+}
+
+/// Index of the final of a consonant that can close a syllable
+fn final_consonant(letter: char) -> Option<u32> {
+    let final_consonant = match letter {
+        '\u{1874}' | '\u{1864}' => 1,
+        '\u{1828}' => 4,
+        '\u{182f}' | '\u{1875}' => 8,
+        '\u{182e}' => 16,
+        '\u{182a}' | '\u{1866}' => 17,
+        '\u{1830}' | '\u{1868}' => 19,
+        '\u{1829}' | '\u{189b}' => 21,
+        _ => return None,
+    };
+    Some(final_consonant)
+}
+
+fn is_vowel(letter: Option<&char>) -> bool {
+    letter.is_some_and(|letter| medial(*letter, Glide::None).is_some())
+}
+
+fn push_syllable(initial: u32, medial: u32, final_consonant: u32, convert_result: &mut String) {
+    let syllable = SYLLABLE_BASE + (initial * 21 + medial) * 28 + final_consonant;
+    convert_result.extend(char::from_u32(syllable));
+}
+
+/// Write the Hangul reading of a letter of Manchu Script, in the style of Qing-era Korean
+/// textbooks of Manchu such as the Cheong-eo Nogeoldae
+///
+/// Each vowel writes a syllable block with the consonant before it as initial, or ㅇ, and the
+/// consonant after it as final if that consonant closes the syllable. The consonants in the block
+/// write nothing.
+/// Initials: g, g' ㄱ, n ㄴ, d ㄷ, l, r ㄹ, m ㅁ, b ㅂ, s, š ㅅ, j, dz ㅈ, c, ts' ㅊ, k, k' ㅋ,
+/// t ㅌ, p, f ㅍ and h, h' ㅎ. y and w are glides written in the vowel, such as ya 야 and wa 와.
+/// ž has the initial ㅈ, and the Ali Gali letters the initial of the native letter closest to them.
+/// Vowels: a ㅏ, e ㅓ, i ㅣ, o ㅗ, u and ū alike ㅜ, and the foreign-word vowel ᡟ ㅡ.
+/// Finals: k, g ㄱ, n ㄴ, l, r ㄹ, m ㅁ, b, p ㅂ, s, t ㅅ and ng, ṅ ㅇ.
+/// Any other consonant writes a block of its own with ㅡ, ng and ṅ 응, and c'y and jy write 츠
+/// and 즈.
+/// `previous` is the letter before it and `following` the letters after it in the word.
+///
+/// Returns `false` if the code point is not a letter.
+pub(crate) fn write_hangul(
+    letter: char,
+    previous: Option<char>,
+    following: &[char],
+    convert_result: &mut String,
+) -> bool {
+    let next = following.first();
+    if let Some(medial_none) = medial(letter, Glide::None) {
+        let (initial, medial) = match previous.and_then(initial) {
+            Some((initial, glide)) => (initial, medial(letter, glide).unwrap_or(medial_none)),
+            None => (SILENT_INITIAL, medial_none),
+        };
+        let final_consonant = next
+            .and_then(|next| final_consonant(*next))
+            .filter(|_| !is_vowel(following.get(1)))
+            .unwrap_or(0);
+        push_syllable(initial, medial, final_consonant, convert_result);
+        return true;
+    }
+    match letter {
+        '\u{1802}' => convert_result.push(','),
+        '\u{1803}' => convert_result.push('.'),
+        '\u{1871}' => push_syllable(14, NEUTRAL_MEDIAL, 0, convert_result),
+        '\u{1872}' => push_syllable(12, NEUTRAL_MEDIAL, 0, convert_result),
+        _ => {
+            let Some((initial, glide)) = initial(letter) else {
+                return false;
+            };
+            let is_initial = is_vowel(next);
+            let is_final = is_vowel(previous.as_ref()) && final_consonant(letter).is_some();
+            if !is_initial && !is_final {
+                let medial = match glide {
+                    Glide::None => NEUTRAL_MEDIAL,
+                    Glide::Y => 20,
+                    Glide::W => 13,
+                };
+                // ㅇ is silent as an initial, so ng is written as a final
+                let final_consonant = match (initial, glide) {
+                    (SILENT_INITIAL, Glide::None) => final_consonant(letter).unwrap_or(0),
+                    _ => 0,
+                };
+                push_syllable(initial, medial, final_consonant, convert_result);
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::Converter;
+    use crate::options::Target;
+
+    #[test]
+    fn hangul() {
+        let converter = Converter::builder().target(Target::Hangul).build();
+        let words = [
+            ("manju", "만주"),
+            ("wesimburengge", "워심부렁거"),
+            ("niyalma", "니얄마"),
+            ("abka", "압카"),
+            ("bithe", "빗허"),
+            ("ambasa", "암바사"),
+            ("cooha", "초오하"),
+            ("takūrafi", "타쿠라피"),
+            ("sefu", "서푸"),
+            ("bolgo", "볼고"),
+            ("ts'c'y", "츠츠"),
+            ("efšen", "어프선"),
+            ("sy", "스"),
+            ("jy", "즈"),
+            ("žo", "조"),
+            ("ṭaḃa", "타바"),
+            ("saṅ", "상"),
+            ("nng", "느응"),
+            ("ṅsa", "응사"),
+        ];
+        for (word, hangul) in words {
+            assert_eq!(converter.convert(word).unwrap(), hangul, "{}", word);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+/// Back vowels a, o and ū, before which k, g and h are uvular
+const BACK_VOWELS: [char; 3] = ['\u{1820}', '\u{1823}', '\u{1861}'];
+
+/// The vowel i, before which s and š are palatalised
+const I: char = '\u{1873}';
+
+/// Reconstructed pronunciation of a letter of Manchu Script in IPA
+///
+/// Follows the descriptive conventions of Gorelova's Manchu Grammar: b, d and g are voiceless
+/// unaspirated against aspirated p, t and k, k, g and h are uvular before a, o and ū and velar
+/// elsewhere, and s and š are palatalised to [ɕ] before i.
+/// The foreign-word vowel ᡟ is [ɨ], and the Ali Gali letters for Sanskrit loans keep the
+/// aspiration and retroflexion of the Sanskrit sounds they write.
+/// `next` is the letter that follows in the word.
+pub(crate) fn ipa_segment(letter: char, next: Option<char>) -> Option<&'static str> {
+    let back = next.is_some_and(|next| BACK_VOWELS.contains(&next));
+    let segment = match letter {
+        '\u{1802}' => ",",
+        '\u{1803}' => ".",
+        '\u{1820}' => "a",
+        '\u{185d}' => "ə",
+        '\u{1873}' => "i",
+        '\u{1823}' => "ɔ",
+        '\u{1860}' => "u",
+        '\u{1861}' => "ʊ",
+        '\u{1828}' => "n",
+        '\u{1829}' => "ŋ",
+        '\u{182a}' => "p",
+        '\u{1866}' => "pʰ",
+        '\u{1830}' if next == Some(I) => "ɕ",
+        '\u{1830}' => "s",
+        '\u{1867}' if next == Some(I) => "ɕ",
+        '\u{1867}' => "ʃ",
+        '\u{1874}' if back => "qʰ",
+        '\u{1874}' => "kʰ",
+        '\u{1864}' if back => "q",
+        '\u{1864}' => "k",
+        '\u{1865}' if back => "χ",
+        '\u{1865}' => "x",
+        '\u{182f}' => "l",
+        '\u{182e}' => "m",
+        '\u{1868}' => "tʰ",
+        '\u{1869}' => "t",
+        '\u{1875}' => "r",
+        '\u{1835}' => "tʃ",
+        '\u{1836}' => "j",
+        '\u{1834}' => "tʃʰ",
+        '\u{1876}' => "f",
+        '\u{1838}' => "w",
+        '\u{186e}' => "tsʰ",
+        '\u{186f}' => "ts",
+        '\u{183b}' => "kʰ",
+        '\u{186c}' => "k",
+        '\u{186d}' => "x",
+        '\u{1871}' => "tsʰɨ",
+        '\u{1872}' => "tsɨ",
+        '\u{1877}' => "ʐ",
+        '\u{185f}' => "ɨ",
+        '\u{189a}' => "ɡʱ",
+        '\u{189b}' => "ŋ",
+        '\u{189c}' => "tʃ",
+        '\u{189d}' => "dʒʱ",
+        '\u{189e}' => "ʈ",
+        '\u{189f}' => "ɖʱ",
+        '\u{18a0}' => "t",
+        '\u{18a1}' => "dʱ",
+        '\u{18a2}' => "ʂ",
+        '\u{18a3}' => "tɕ",
+        '\u{18a4}' => "ʐ",
+        '\u{18a5}' => "z",
+        '\u{18a8}' => "bʱ",
+        '\u{18aa}' => "ɭ",
+        _ => return None,
+    };
+    Some(segment)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::options::Target;
+
+    #[test]
+    fn allophones() {
+        let ipa = |script: &str| {
+            let mut result = String::new();
+            Target::Ipa.render(script, &mut result);
+            result
+        };
+        // kaka, keke: uvular before a, velar before e
+        assert_eq!(ipa("ᡴᠠᡴᡝ"), "qʰakʰə");
+        assert_eq!(ipa("ᡤᠣᡤᡳ"), "qɔki");
+        assert_eq!(ipa("ᡥᡡᡥᡠ"), "χʊxu");
+        // si, ši and sa, ša
+        assert_eq!(ipa("ᠰᡳᡧᡳᠰᠠᡧᠠ"), "ɕiɕisaʃa");
+        // A final consonant has no following vowel
+        assert_eq!(ipa("ᠠᡴ"), "akʰ");
+        assert_eq!(ipa("ᠠQ"), "aQ");
+    }
+}
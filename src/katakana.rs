@@ -0,0 +1,110 @@
+/// Kana of a consonant for each following vowel, in the order a, e, i, o, u, and for the end of a syllable
+///
+/// ū is written with the kana of u followed by "ー". The foreign-word and Ali Gali letters share
+/// the row of the native letter closest to them.
+fn consonant_row(letter: char) -> Option<[&'static str; 6]> {
+    let row = match letter {
+        '\u{1828}' => ["ナ", "ネ", "ニ", "ノ", "ヌ", "ン"],
+        '\u{1829}' | '\u{189b}' => ["ンガ", "ンゲ", "ンギ", "ンゴ", "ング", "ン"],
+        '\u{182a}' | '\u{18a8}' => ["バ", "ベ", "ビ", "ボ", "ブ", "ブ"],
+        '\u{1866}' => ["パ", "ペ", "ピ", "ポ", "プ", "プ"],
+        '\u{1830}' => ["サ", "セ", "シ", "ソ", "ス", "ス"],
+        '\u{1867}' | '\u{18a2}' => ["シャ", "シェ", "シ", "ショ", "シュ", "シュ"],
+        '\u{1874}' | '\u{183b}' => ["カ", "ケ", "キ", "コ", "ク", "ク"],
+        '\u{1864}' | '\u{186c}' | '\u{189a}' => ["ガ", "ゲ", "ギ", "ゴ", "グ", "グ"],
+        '\u{1865}' | '\u{186d}' => ["ハ", "ヘ", "ヒ", "ホ", "フ", "フ"],
+        '\u{182f}' | '\u{1875}' | '\u{18aa}' => ["ラ", "レ", "リ", "ロ", "ル", "ル"],
+        '\u{182e}' => ["マ", "メ", "ミ", "モ", "ム", "ム"],
+        '\u{1868}' | '\u{189e}' | '\u{18a0}' => ["タ", "テ", "ティ", "ト", "トゥ", "ト"],
+        '\u{1869}' | '\u{189f}' | '\u{18a1}' => ["ダ", "デ", "ディ", "ド", "ドゥ", "ド"],
+        '\u{1835}' | '\u{1877}' | '\u{189d}' | '\u{18a4}' => {
+            ["ジャ", "ジェ", "ジ", "ジョ", "ジュ", "ジュ"]
+        }
+        '\u{1836}' => ["ヤ", "イェ", "イ", "ヨ", "ユ", "イ"],
+        '\u{1834}' | '\u{189c}' | '\u{18a3}' => ["チャ", "チェ", "チ", "チョ", "チュ", "チュ"],
+        '\u{1876}' => ["ファ", "フェ", "フィ", "フォ", "フ", "フ"],
+        '\u{1838}' => ["ワ", "ウェ", "ウィ", "ウォ", "ウ", "ウ"],
+        '\u{186e}' => ["ツァ", "ツェ", "ツィ", "ツォ", "ツ", "ツ"],
+        '\u{186f}' | '\u{18a5}' => ["ザ", "ゼ", "ズィ", "ゾ", "ズ", "ズ"],
+        _ => return None,
+    };
+    Some(row)
+}
+
+/// Column of a vowel in [`consonant_row`], with ū and the foreign-word vowel ᡟ in the column of u
+fn vowel_column(letter: char) -> Option<usize> {
+    match letter {
+        '\u{1820}' => Some(0),
+        '\u{185d}' => Some(1),
+        '\u{1873}' => Some(2),
+        '\u{1823}' => Some(3),
+        '\u{1860}' | '\u{1861}' | '\u{185f}' => Some(4),
+        _ => None,
+    }
+}
+
+/// Katakana reading of a letter of Manchu Script
+///
+/// Follows the conventions of Japanese-language Manchu dictionaries: a consonant and the vowel
+/// after it are read as one kana syllable, which the consonant carries and the vowel adds nothing
+/// to, except ū, which adds the long vowel mark "ー".
+/// A consonant at the end of a syllable is read with its u-row kana, t and d with their o-row kana,
+/// y with "イ", and n and ng as "ン".
+/// `previous` and `next` are the letters around it in the word.
+pub(crate) fn kana(
+    letter: char,
+    previous: Option<char>,
+    next: Option<char>,
+) -> Option<&'static str> {
+    if let Some(column) = vowel_column(letter) {
+        let after_consonant = previous.and_then(consonant_row).is_some();
+        let kana = match (after_consonant, letter) {
+            (true, '\u{1861}') => "ー",
+            (true, _) => "",
+            (false, '\u{1861}') => "ウー",
+            (false, _) => ["ア", "エ", "イ", "オ", "ウ"][column],
+        };
+        return Some(kana);
+    }
+    let kana = match letter {
+        '\u{1802}' => "、",
+        '\u{1803}' => "。",
+        // c'y and jy carry their own vowel
+        '\u{1871}' => "ツ",
+        '\u{1872}' => "ズ",
+        letter => consonant_row(letter)?[next.and_then(vowel_column).unwrap_or(5)],
+    };
+    Some(kana)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::converter::Converter;
+    use crate::options::Target;
+
+    #[test]
+    fn katakana() {
+        let converter = Converter::builder().target(Target::Katakana).build();
+        let words = [
+            ("manju", "マンジュ"),
+            ("gisun", "ギスン"),
+            ("takūrafi", "タクーラフィ"),
+            ("ūren", "ウーレン"),
+            ("bithe", "ビトヘ"),
+            ("abka", "アブカ"),
+            ("cooha", "チョオハ"),
+            ("niyalma", "ニヤルマ"),
+            ("wesimburengge", "ウェシムブレンゲ"),
+            ("šolo.", "ショロ。"),
+            ("sy", "ス"),
+            ("dzy", "ズ"),
+            ("jy", "ズ"),
+            ("žo", "ジョ"),
+            ("ṭaḍa", "タダ"),
+            ("ṅaḃa", "ンガバ"),
+        ];
+        for (word, kana) in words {
+            assert_eq!(converter.convert(word).unwrap(), kana, "{}", word);
+        }
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::converter::{for_each_word, Conversion};
+use crate::error::{ConversionError, Locator};
+use crate::latin_manchu_unicode_mapper::{
+    get_latin_manchu_map, get_scheme_latin_map, get_scheme_table, LatinManchuTable,
+};
+use crate::options::Scheme;
+
+/// U+202F NARROW NO-BREAK SPACE, which joins suffixes to words in Manchu Script
+const NNBSP: char = '\u{202f}';
+
+/// Maximum number of code points in a letter
+const MAX_LETTER_LEN: usize = 4;
+
+pub trait LatinConverter {
+    /// Convert Manchu Script to Möllendorff transliteration and return a String
+    ///
+    /// Code points without a Latin spelling, such as free variation selectors, MVS and NNBSP, are errors.
+    /// So are letters whose spelling reads back as other letters, such as "ᠨᡤ" spelled "ng".
+    /// The output converts back to the same Manchu Script.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use manchu_converter::{LatinConverter, ManchuConverter};
+    ///
+    /// fn main() {
+    ///     let text = "ᠸᡝᠰᡳᠮᠪᡠᡵᡝᠩᡤᡝ";
+    ///     let result = text.convert_to_latin().unwrap();
+    ///     assert_eq!(result, "wesimburengge");
+    ///     assert_eq!(result.convert_to_manchu(&None).unwrap(), text)
+    /// }
+    fn convert_to_latin(&self) -> Result<String, ConversionError>;
+
+    /// Convert Manchu Script to Möllendorff transliteration and collect every error
+    ///
+    /// Code points without a Latin spelling are left as they are.
+    fn convert_to_latin_with_diagnostics(&self) -> Conversion;
+
+    /// Convert Manchu Script to the spelling of `scheme` and return a String
+    ///
+    /// Works like [`LatinConverter::convert_to_latin`], and a `scheme` of [`Scheme::Auto`] spells
+    /// like [`Scheme::Mollendorff`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use manchu_converter::{LatinConverter, Scheme};
+    ///
+    /// fn main() {
+    ///     let result = "ᡨᠠᡴᡡᡵᠠᡶᡳ ᠵᡠᡤᡡᠨ".convert_to_scheme(Scheme::Cyrillic).unwrap();
+    ///     assert_eq!(result, "такӯрафи джугӯн")
+    /// }
+    fn convert_to_scheme(&self, scheme: Scheme) -> Result<String, ConversionError>;
+
+    /// Convert Manchu Script to the spelling of `scheme` and collect every error
+    fn convert_to_scheme_with_diagnostics(&self, scheme: Scheme) -> Conversion;
+}
+
+impl LatinConverter for str {
+    #[inline]
+    fn convert_to_latin(&self) -> Result<String, ConversionError> {
+        self.convert_to_scheme(Scheme::Mollendorff)
+    }
+
+    #[inline]
+    fn convert_to_latin_with_diagnostics(&self) -> Conversion {
+        self.convert_to_scheme_with_diagnostics(Scheme::Mollendorff)
+    }
+
+    #[inline]
+    fn convert_to_scheme(&self, scheme: Scheme) -> Result<String, ConversionError> {
+        let result = self.convert_to_scheme_with_diagnostics(scheme);
+        match result.errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(result.output),
+        }
+    }
+
+    #[inline]
+    fn convert_to_scheme_with_diagnostics(&self, scheme: Scheme) -> Conversion {
+        let table = get_scheme_table(scheme, scheme.default_ascii_profiles())
+            .unwrap_or_else(|| LatinManchuTable::new(get_latin_manchu_map()));
+        let mut errors = Vec::new();
+        let mut output = String::with_capacity(self.len());
+        script_to_latin(
+            self,
+            get_scheme_latin_map(scheme),
+            &table,
+            &mut errors,
+            &mut output,
+        );
+        Conversion {
+            output,
+            errors,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// Convert the words of Manchu Script and copy every whitespace character as it is
+fn script_to_latin(
+    text: &str,
+    manchu_latin_map: &HashMap<&str, &str>,
+    table: &LatinManchuTable,
+    errors: &mut Vec<ConversionError>,
+    convert_result: &mut String,
+) {
+    let mut locator = Locator::new(text);
+    // NNBSP belongs to the word it joins a suffix to
+    let is_separator = |c: char| c.is_whitespace() && c != NNBSP;
+    for_each_word(
+        text,
+        is_separator,
+        convert_result,
+        |offset, word, convert_result| {
+            word_to_latin(
+                &mut locator,
+                offset,
+                word,
+                manchu_latin_map,
+                table,
+                errors,
+                convert_result,
+            )
+        },
+    );
+}
+
+/// Convert a word at `offset` in the text of `locator` and append it to `convert_result`
+fn word_to_latin(
+    locator: &mut Locator,
+    offset: usize,
+    word: &str,
+    manchu_latin_map: &HashMap<&str, &str>,
+    table: &LatinManchuTable,
+    errors: &mut Vec<ConversionError>,
+    convert_result: &mut String,
+) {
+    let mut spelled = Spelling::with_capacity(word.len());
+    let mut i = 0;
+    while i < word.len() {
+        match longest_letter(&word[i..], manchu_latin_map) {
+            Some((len, spelling)) => {
+                let end = i + len;
+                spelled.push(&word[i..end], i..end, spelling);
+                convert_result.push_str(spelling);
+                i = end;
+            }
+            None => {
+                let c = word[i..].chars().next().unwrap_or_default();
+                let end = i + c.len_utf8();
+                errors.push(ConversionError::UnsupportedCodePoint {
+                    word: word.to_string(),
+                    grapheme: c.to_string(),
+                    span: locator.locate(offset + i, offset + end),
+                });
+                convert_result.push(c);
+                i = end;
+            }
+        }
+    }
+    spelled.check(locator, offset, word, table, errors);
+}
+
+/// Match the longest letter of Manchu Script at the start of `script`, which can be written as
+/// several code points
+///
+/// Returns the length of the letter in bytes and its spelling.
+fn longest_letter<'a>(
+    script: &str,
+    manchu_latin_map: &HashMap<&str, &'a str>,
+) -> Option<(usize, &'a str)> {
+    let ends = script
+        .char_indices()
+        .take(MAX_LETTER_LEN)
+        .map(|(i, c)| i + c.len_utf8())
+        .collect::<Vec<_>>();
+    ends.into_iter().rev().find_map(|end| {
+        manchu_latin_map
+            .get(&script[..end])
+            .map(|spelling| (end, *spelling))
+    })
+}
+
+/// Spelling of the letters of a word, without what is left as it is
+pub(crate) struct Spelling<'a> {
+    latin: String,
+    /// Each letter with its byte ranges in the word and in `latin`
+    letters: Vec<(&'a str, Range<usize>, Range<usize>)>,
+}
+
+impl<'a> Spelling<'a> {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Spelling {
+            latin: String::with_capacity(capacity),
+            letters: Vec::new(),
+        }
+    }
+
+    /// Spell `letter`, which is at the byte range `input` in the word, as `unit`
+    pub(crate) fn push(&mut self, letter: &'a str, input: Range<usize>, unit: &str) {
+        let spelling = self.latin.len()..self.latin.len() + unit.len();
+        self.letters.push((letter, input, spelling));
+        self.latin.push_str(unit);
+    }
+
+    /// Read the spelling of a word at `offset` in the text of `locator` back with `table` and
+    /// report the letters that it gives other letters for
+    pub(crate) fn check(
+        &self,
+        locator: &mut Locator,
+        offset: usize,
+        word: &str,
+        table: &LatinManchuTable,
+        errors: &mut Vec<ConversionError>,
+    ) {
+        let (latin, letters) = (&self.latin, &self.letters);
+        let mut k = 0;
+        while k < letters.len() {
+            let (letter, _, spelling) = &letters[k];
+            let matched = table.longest_match(&latin[spelling.start..]);
+            if matched == Some((spelling.len(), *letter)) {
+                k += 1;
+                continue;
+            }
+            let matched_end = spelling.start + matched.map_or(spelling.len(), |(len, _)| len);
+            let last = letters[k..]
+                .iter()
+                .position(|(_, _, spelling)| spelling.end >= matched_end)
+                .map_or(letters.len() - 1, |position| k + position);
+            let input = letters[k].1.start..letters[last].1.end;
+            errors.push(ConversionError::AmbiguousSpelling {
+                word: word.to_string(),
+                grapheme: word[input.clone()].to_string(),
+                spelling: latin[spelling.start..letters[last].2.end].to_string(),
+                span: locator.locate(offset + input.start, offset + input.end),
+            });
+            k = last + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::{Converter, ManchuConverter};
+    use crate::error::Span;
+    use crate::latin_manchu_unicode_mapper::LATIN_MANCHU;
+
+    #[test]
+    fn convert_to_latin() {
+        assert_eq!(
+            "ᠴᠣᠣᡥᠠ ᠪᡝ\nᠠᠴᠠᡥᠠ᠃".convert_to_latin().unwrap(),
+            "cooha be\nacaha."
+        );
+        assert_eq!("ᡨᠠᡴᡡᡵᠠᡶᡳ".convert_to_latin().unwrap(), "tak\u{16b}rafi");
+        assert_eq!("\u{1871}\u{186e}".convert_to_latin().unwrap(), "c'yts'");
+    }
+
+    #[test]
+    fn convert_to_cyrillic() {
+        let text = "ᠴᠣᠣᡥᠠ ᠪᡝ ᡨᠠᡴᡡᡵᠠᡶᡳ᠃ \u{1871}\u{186e}\u{186f}";
+        let cyrillic = text.convert_to_scheme(Scheme::Cyrillic).unwrap();
+        assert_eq!(cyrillic, "чооха бэ такӯрафи. чыцдз");
+        assert_eq!(
+            Converter::builder()
+                .scheme(Scheme::Cyrillic)
+                .build()
+                .convert(&cyrillic)
+                .unwrap(),
+            text
+        );
+        assert!("ᠰᠠᠨᡤᡝ".convert_to_scheme(Scheme::Cyrillic).is_err());
+        assert_eq!(
+            "ᡨᠠᡴᡡᡵᠠᡶᡳ"
+                .convert_to_scheme(Scheme::MollendorffAscii)
+                .unwrap(),
+            "takvrafi"
+        );
+    }
+
+    #[test]
+    fn unsupported_code_points() {
+        let text = "ᠮᠠᠨᠵᡠ\u{180b} ᠪᡝ\u{180e}ᡳ ᠨᡳᠶᠠᠯᠮᠠ\u{202f}ᡳ";
+        let result = text.convert_to_latin_with_diagnostics();
+        assert_eq!(result.output, "manju\u{180b} be\u{180e}i niyalma\u{202f}i");
+        let graphemes = result
+            .errors
+            .iter()
+            .map(|error| (error.grapheme(), error.span().column))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            graphemes,
+            vec![("\u{180b}", 6), ("\u{180e}", 10), ("\u{202f}", 20)]
+        );
+    }
+
+    #[test]
+    fn ambiguous_spelling() {
+        let text = "ᠰᠠᠨᡤᡝ";
+        let error = text.convert_to_latin().unwrap_err();
+        assert_eq!(
+            error,
+            ConversionError::AmbiguousSpelling {
+                word: text.to_string(),
+                grapheme: "ᠨᡤ".to_string(),
+                spelling: "ng".to_string(),
+                span: Span {
+                    line: 1,
+                    column: 3,
+                    start: 6,
+                    end: 12
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let letters = LATIN_MANCHU
+            .units
+            .iter()
+            .map(|(_, unicode)| *unicode)
+            .collect::<Vec<_>>();
+        for a in &letters {
+            for b in &letters {
+                let script = [*a, *b].concat();
+                let result = script.convert_to_latin_with_diagnostics();
+                if result.has_errors() {
+                    // "n" followed by "g" or "g'" reads back as "ng", and a consonant followed
+                    // by "y" as a syllable such as "sy"
+                    let syllable = ["sy", "jy", "cy", "dzy", "ts'y", "žy"]
+                        .iter()
+                        .any(|syllable| result.output.starts_with(syllable));
+                    assert!(
+                        result.output.starts_with("ng") || syllable,
+                        "{}",
+                        result.output
+                    );
+                    continue;
+                }
+                assert_eq!(result.output.convert_to_manchu(&None).unwrap(), script);
+            }
+        }
+    }
+}
@@ -1,42 +1,1370 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
-pub fn get_latin_manchu_map<'a>() -> HashMap<&'a str, u16> {
-    HashMap::from([
-        (",", 0x1802),
-        (".", 0x1803),
-        ("a", 0x1820),
-        ("e", 0x185d),
-        ("i", 0x1873),
-        ("o", 0x1823),
-        ("u", 0x1860),
-        ("ū", 0x1861),
-        ("ū", 0x1861),
-        ("v", 0x1861),
-        ("n", 0x1828),
-        ("ng", 0x1829),
-        ("b", 0x182a),
-        ("p", 0x1866),
-        ("s", 0x1830),
-        ("š", 0x1867),
-        ("x", 0x1867),
-        ("k", 0x1874),
-        ("g", 0x1864),
-        ("h", 0x1865),
-        ("l", 0x182f),
-        ("m", 0x182e),
-        ("t", 0x1868),
-        ("d", 0x1869),
-        ("r", 0x1875),
-        ("j", 0x1835),
-        ("y", 0x1836),
-        ("c", 0x1834),
-        ("f", 0x1876),
-        ("w", 0x1838),
-        ("ts'", 0x186e),
-        ("dz", 0x186f),
-        ("k'", 0x183b),
-        ("g'", 0x186c),
-        ("h'", 0x186d),
-        ("c'y", 0x1871),
-    ])
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::options::{AsciiProfile, Scheme};
+
+/// Maximum number of graphemes in a unit
+const MAX_UNIT_LEN: usize = 8;
+
+/// Mapping table from Latin units to code points, built at compile time
+///
+/// A unit is mostly written as one code point, but can be written as several, such as "sy" as a
+/// consonant and a vowel.
+#[derive(Debug)]
+pub struct UnitTable {
+    /// Every unit and its code points
+    pub units: &'static [(&'static str, &'static str)],
+    /// Lookup compiled to a `match` over `units`
+    pub lookup: fn(&str) -> Option<&'static str>,
+}
+
+/// Define a static [`UnitTable`] from `unit => code points` entries
+macro_rules! unit_table {
+    ($(#[$meta:meta])* $name:ident { $($unit:literal => $unicode:literal,)* }) => {
+        $(#[$meta])*
+        pub static $name: UnitTable = UnitTable {
+            units: &[$(($unit, $unicode),)*],
+            lookup: |unit| match unit {
+                $($unit => Some($unicode),)*
+                _ => None,
+            },
+        };
+    };
+}
+
+unit_table! {
+    /// Möllendorff transliteration
+    ///
+    /// The foreign-word letters for Chinese loans are ts', dz, ž, k', g' and h', with "sy", "dzy",
+    /// "ts'y" and "žy" for a consonant with the vowel ᡟ, and "jy" and "c'y", or "cy", for the
+    /// syllables written as one letter.
+    /// The Ali Gali letters for Sanskrit loans are written with a dot above for letters that
+    /// double a native one, ġ, ċ, ṫ, ḋ, ż and ḃ, a dot below for retroflexes, ṭ, ḍ, ṣ, ẓ and ḷ,
+    /// and ṅ, ǰ and ć for nga, jha and cya.
+    LATIN_MANCHU {
+        "," => "\u{1802}",
+        "." => "\u{1803}",
+        "a" => "\u{1820}",
+        "e" => "\u{185d}",
+        "i" => "\u{1873}",
+        "o" => "\u{1823}",
+        "u" => "\u{1860}",
+        "\u{16b}" => "\u{1861}",
+        "n" => "\u{1828}",
+        "ng" => "\u{1829}",
+        "b" => "\u{182a}",
+        "p" => "\u{1866}",
+        "s" => "\u{1830}",
+        "\u{161}" => "\u{1867}",
+        "k" => "\u{1874}",
+        "g" => "\u{1864}",
+        "h" => "\u{1865}",
+        "l" => "\u{182f}",
+        "m" => "\u{182e}",
+        "t" => "\u{1868}",
+        "d" => "\u{1869}",
+        "r" => "\u{1875}",
+        "j" => "\u{1835}",
+        "y" => "\u{1836}",
+        "c" => "\u{1834}",
+        "f" => "\u{1876}",
+        "w" => "\u{1838}",
+        "ts'" => "\u{186e}",
+        "dz" => "\u{186f}",
+        "k'" => "\u{183b}",
+        "g'" => "\u{186c}",
+        "h'" => "\u{186d}",
+        "c'y" => "\u{1871}",
+        "cy" => "\u{1871}",
+        "jy" => "\u{1872}",
+        "\u{17e}" => "\u{1877}",
+        "sy" => "\u{1830}\u{185f}",
+        "dzy" => "\u{186f}\u{185f}",
+        "ts'y" => "\u{186e}\u{185f}",
+        "\u{17e}y" => "\u{1877}\u{185f}",
+        "\u{121}" => "\u{189a}",
+        "\u{1e45}" => "\u{189b}",
+        "\u{10b}" => "\u{189c}",
+        "\u{1f0}" => "\u{189d}",
+        "\u{1e6d}" => "\u{189e}",
+        "\u{1e0d}" => "\u{189f}",
+        "\u{1e6b}" => "\u{18a0}",
+        "\u{1e0b}" => "\u{18a1}",
+        "\u{1e63}" => "\u{18a2}",
+        "\u{107}" => "\u{18a3}",
+        "\u{1e93}" => "\u{18a4}",
+        "\u{17c}" => "\u{18a5}",
+        "\u{1e03}" => "\u{18a8}",
+        "\u{1e37}" => "\u{18aa}",
+    }
+}
+
+unit_table! {
+    /// Möllendorff transliteration written in Sibe orthography
+    ///
+    /// Sibe writes i, k, j, f and ž with the SIBE letters, r with the Mongolian letter and ng with
+    /// SIBE ANG, and shares the other letters with Manchu. Both ž and jy are SIBE ZHA.
+    LATIN_SIBE {
+        "," => "\u{1802}",
+        "." => "\u{1803}",
+        "a" => "\u{1820}",
+        "e" => "\u{185d}",
+        "i" => "\u{185e}",
+        "o" => "\u{1823}",
+        "u" => "\u{1860}",
+        "\u{16b}" => "\u{1861}",
+        "n" => "\u{1828}",
+        "ng" => "\u{1862}",
+        "b" => "\u{182a}",
+        "p" => "\u{1866}",
+        "s" => "\u{1830}",
+        "\u{161}" => "\u{1867}",
+        "k" => "\u{1863}",
+        "g" => "\u{1864}",
+        "h" => "\u{1865}",
+        "l" => "\u{182f}",
+        "m" => "\u{182e}",
+        "t" => "\u{1868}",
+        "d" => "\u{1869}",
+        "r" => "\u{1837}",
+        "j" => "\u{186a}",
+        "y" => "\u{1836}",
+        "c" => "\u{1834}",
+        "f" => "\u{186b}",
+        "w" => "\u{1838}",
+        "ts'" => "\u{186e}",
+        "dz" => "\u{186f}",
+        "k'" => "\u{183b}",
+        "g'" => "\u{186c}",
+        "h'" => "\u{186d}",
+        "c'y" => "\u{1871}",
+        "jy" => "\u{1872}",
+        "\u{17e}" => "\u{1872}",
+    }
+}
+
+unit_table! {
+    /// Abkai romanization, with "uu" for ū, "x" for š, doubled letters for k', g' and h',
+    /// and "ts", "z" and "ch" for ts', dz and c'y
+    ABKAI_MANCHU {
+        "," => "\u{1802}",
+        "." => "\u{1803}",
+        "a" => "\u{1820}",
+        "e" => "\u{185d}",
+        "i" => "\u{1873}",
+        "o" => "\u{1823}",
+        "u" => "\u{1860}",
+        "uu" => "\u{1861}",
+        "n" => "\u{1828}",
+        "ng" => "\u{1829}",
+        "b" => "\u{182a}",
+        "p" => "\u{1866}",
+        "s" => "\u{1830}",
+        "x" => "\u{1867}",
+        "k" => "\u{1874}",
+        "g" => "\u{1864}",
+        "h" => "\u{1865}",
+        "l" => "\u{182f}",
+        "m" => "\u{182e}",
+        "t" => "\u{1868}",
+        "d" => "\u{1869}",
+        "r" => "\u{1875}",
+        "j" => "\u{1835}",
+        "y" => "\u{1836}",
+        "c" => "\u{1834}",
+        "f" => "\u{1876}",
+        "w" => "\u{1838}",
+        "ts" => "\u{186e}",
+        "z" => "\u{186f}",
+        "kk" => "\u{183b}",
+        "gg" => "\u{186c}",
+        "hh" => "\u{186d}",
+        "ch" => "\u{1871}",
+    }
+}
+
+unit_table! {
+    /// Xinmanhan romanization, with "v" for ū, "x" for š, "q" for c, doubled letters for k', g' and h',
+    /// and "c", "z" and "ch" for ts', dz and c'y
+    XINMANHAN_MANCHU {
+        "," => "\u{1802}",
+        "." => "\u{1803}",
+        "a" => "\u{1820}",
+        "e" => "\u{185d}",
+        "i" => "\u{1873}",
+        "o" => "\u{1823}",
+        "u" => "\u{1860}",
+        "v" => "\u{1861}",
+        "n" => "\u{1828}",
+        "ng" => "\u{1829}",
+        "b" => "\u{182a}",
+        "p" => "\u{1866}",
+        "s" => "\u{1830}",
+        "x" => "\u{1867}",
+        "k" => "\u{1874}",
+        "g" => "\u{1864}",
+        "h" => "\u{1865}",
+        "l" => "\u{182f}",
+        "m" => "\u{182e}",
+        "t" => "\u{1868}",
+        "d" => "\u{1869}",
+        "r" => "\u{1875}",
+        "j" => "\u{1835}",
+        "y" => "\u{1836}",
+        "q" => "\u{1834}",
+        "f" => "\u{1876}",
+        "w" => "\u{1838}",
+        "c" => "\u{186e}",
+        "z" => "\u{186f}",
+        "kk" => "\u{183b}",
+        "gg" => "\u{186c}",
+        "hh" => "\u{186d}",
+        "ch" => "\u{1871}",
+    }
+}
+
+unit_table! {
+    /// Xibe romanization of modern Sibe publications, with "v" for ū, "x" for š, "zh" and "ch"
+    /// for j and c, "z" and "c" for dz and ts', doubled letters for k', g' and h', and "chy" for
+    /// c'y
+    XIBE_MANCHU {
+        "," => "\u{1802}",
+        "." => "\u{1803}",
+        "a" => "\u{1820}",
+        "e" => "\u{185d}",
+        "i" => "\u{1873}",
+        "o" => "\u{1823}",
+        "u" => "\u{1860}",
+        "v" => "\u{1861}",
+        "n" => "\u{1828}",
+        "ng" => "\u{1829}",
+        "b" => "\u{182a}",
+        "p" => "\u{1866}",
+        "s" => "\u{1830}",
+        "x" => "\u{1867}",
+        "k" => "\u{1874}",
+        "g" => "\u{1864}",
+        "h" => "\u{1865}",
+        "l" => "\u{182f}",
+        "m" => "\u{182e}",
+        "t" => "\u{1868}",
+        "d" => "\u{1869}",
+        "r" => "\u{1875}",
+        "zh" => "\u{1835}",
+        "y" => "\u{1836}",
+        "ch" => "\u{1834}",
+        "f" => "\u{1876}",
+        "w" => "\u{1838}",
+        "c" => "\u{186e}",
+        "z" => "\u{186f}",
+        "kk" => "\u{183b}",
+        "gg" => "\u{186c}",
+        "hh" => "\u{186d}",
+        "chy" => "\u{1871}",
+    }
+}
+
+unit_table! {
+    /// Zakharov-style Cyrillic transcription, with "э" and also "е" for e, "ӯ" for ū, "нг" for ng,
+    /// "дж" for j, "ч" for c, and "ц", "дз" and "чы" for ts', dz and c'y
+    CYRILLIC_MANCHU {
+        "," => "\u{1802}",
+        "." => "\u{1803}",
+        "\u{430}" => "\u{1820}",
+        "\u{44d}" => "\u{185d}",
+        "\u{435}" => "\u{185d}",
+        "\u{438}" => "\u{1873}",
+        "\u{43e}" => "\u{1823}",
+        "\u{443}" => "\u{1860}",
+        "\u{4ef}" => "\u{1861}",
+        "\u{43d}" => "\u{1828}",
+        "\u{43d}\u{433}" => "\u{1829}",
+        "\u{431}" => "\u{182a}",
+        "\u{43f}" => "\u{1866}",
+        "\u{441}" => "\u{1830}",
+        "\u{448}" => "\u{1867}",
+        "\u{43a}" => "\u{1874}",
+        "\u{433}" => "\u{1864}",
+        "\u{445}" => "\u{1865}",
+        "\u{43b}" => "\u{182f}",
+        "\u{43c}" => "\u{182e}",
+        "\u{442}" => "\u{1868}",
+        "\u{434}" => "\u{1869}",
+        "\u{440}" => "\u{1875}",
+        "\u{434}\u{436}" => "\u{1835}",
+        "\u{439}" => "\u{1836}",
+        "\u{447}" => "\u{1834}",
+        "\u{444}" => "\u{1876}",
+        "\u{432}" => "\u{1838}",
+        "\u{446}" => "\u{186e}",
+        "\u{434}\u{437}" => "\u{186f}",
+        "\u{43a}'" => "\u{183b}",
+        "\u{433}'" => "\u{186c}",
+        "\u{445}'" => "\u{186d}",
+        "\u{447}\u{44b}" => "\u{1871}",
+    }
+}
+
+unit_table! {
+    /// Classical Mongolian transliteration of Mongolian letters U+1820 to U+1842
+    ///
+    /// Both q and k are QA and both γ and g are GA, which the script does not tell apart. The Galik
+    /// letters are f, k', kh, c, z, h, ž, lh, zhi and chi, "j" is also read as ǰ, and "-" joins a
+    /// suffix with U+202F NARROW NO-BREAK SPACE.
+    MONGOLIAN {
+        "," => "\u{1802}",
+        "." => "\u{1803}",
+        "-" => "\u{202f}",
+        "a" => "\u{1820}",
+        "e" => "\u{1821}",
+        "i" => "\u{1822}",
+        "o" => "\u{1823}",
+        "u" => "\u{1824}",
+        "\u{f6}" => "\u{1825}",
+        "\u{fc}" => "\u{1826}",
+        "\u{113}" => "\u{1827}",
+        "n" => "\u{1828}",
+        "ng" => "\u{1829}",
+        "b" => "\u{182a}",
+        "p" => "\u{182b}",
+        "q" => "\u{182c}",
+        "k" => "\u{182c}",
+        "\u{3b3}" => "\u{182d}",
+        "g" => "\u{182d}",
+        "m" => "\u{182e}",
+        "l" => "\u{182f}",
+        "s" => "\u{1830}",
+        "\u{161}" => "\u{1831}",
+        "t" => "\u{1832}",
+        "d" => "\u{1833}",
+        "\u{10d}" => "\u{1834}",
+        "\u{1f0}" => "\u{1835}",
+        "j" => "\u{1835}",
+        "y" => "\u{1836}",
+        "r" => "\u{1837}",
+        "w" => "\u{1838}",
+        "f" => "\u{1839}",
+        "k'" => "\u{183a}",
+        "kh" => "\u{183b}",
+        "c" => "\u{183c}",
+        "z" => "\u{183d}",
+        "h" => "\u{183e}",
+        "\u{17e}" => "\u{183f}",
+        "lh" => "\u{1840}",
+        "zhi" => "\u{1841}",
+        "chi" => "\u{1842}",
+    }
+}
+
+unit_table! {
+    /// Letters of the shared inventory in Mongolian Script
+    ///
+    /// A letter that Manchu also has is named by its unit in [`LATIN_MANCHU`], as QA by k, GA by g,
+    /// TSA by ts' and the Galik zhi and chi by jy and c'y, and the other letters by their unit in
+    /// [`MONGOLIAN`].
+    MONGOLIAN_LETTERS {
+        "," => "\u{1802}",
+        "." => "\u{1803}",
+        "-" => "\u{202f}",
+        "a" => "\u{1820}",
+        "e" => "\u{1821}",
+        "i" => "\u{1822}",
+        "o" => "\u{1823}",
+        "u" => "\u{1824}",
+        "\u{f6}" => "\u{1825}",
+        "\u{fc}" => "\u{1826}",
+        "\u{113}" => "\u{1827}",
+        "n" => "\u{1828}",
+        "ng" => "\u{1829}",
+        "b" => "\u{182a}",
+        "p" => "\u{182b}",
+        "k" => "\u{182c}",
+        "g" => "\u{182d}",
+        "m" => "\u{182e}",
+        "l" => "\u{182f}",
+        "s" => "\u{1830}",
+        "\u{161}" => "\u{1831}",
+        "t" => "\u{1832}",
+        "d" => "\u{1833}",
+        "c" => "\u{1834}",
+        "j" => "\u{1835}",
+        "y" => "\u{1836}",
+        "r" => "\u{1837}",
+        "w" => "\u{1838}",
+        "f" => "\u{1839}",
+        "k'" => "\u{183a}",
+        "kh" => "\u{183b}",
+        "ts'" => "\u{183c}",
+        "dz" => "\u{183d}",
+        "h" => "\u{183e}",
+        "\u{17e}" => "\u{183f}",
+        "lh" => "\u{1840}",
+        "jy" => "\u{1841}",
+        "c'y" => "\u{1842}",
+    }
+}
+
+unit_table! {
+    /// Hanyu Pinyin of Chinese loanwords, spelled the way Manchu writes Chinese
+    ///
+    /// Initials: zh and j as j, ch and q as c, sh as š, r as ž, z as dz, c as ts', x as s.
+    /// The apical vowel of zhi, chi, ri, zi, ci and si is the foreign-word vowel "y", as in jy, cy,
+    /// žy, dzy, ts'y and sy, while shi is ši.
+    /// Finals: ou as eo, ong as ung, uo as o, ie as iye, ia as iya, iu as io, ua as uwa, ü as ioi,
+    /// üan as iowan, üe as iowe, ün as iyūn, yi and wu as i and u, and the rest letter by letter.
+    /// The syllable er is el. As a syllable of its own er is never followed by a vowel, so an e
+    /// followed by r and a vowel ends its syllable before a syllable of the initial r.
+    /// After y the i of ü is left out, as in yuwan, yuwe and yūn.
+    /// An apostrophe separates syllables, as in xi'an.
+    PINYIN_MANCHU {
+        "," => "\u{1802}",
+        "." => "\u{1803}",
+        "'" => "",
+        "a" => "\u{1820}",
+        "e" => "\u{185d}",
+        "i" => "\u{1873}",
+        "o" => "\u{1823}",
+        "u" => "\u{1860}",
+        "\u{fc}" => "\u{1873}\u{1823}\u{1873}",
+        "v" => "\u{1873}\u{1823}\u{1873}",
+        "ou" => "\u{185d}\u{1823}",
+        "ong" => "\u{1860}\u{1829}",
+        "uo" => "\u{1823}",
+        "ie" => "\u{1873}\u{1836}\u{185d}",
+        "ia" => "\u{1873}\u{1836}\u{1820}",
+        "iu" => "\u{1873}\u{1823}",
+        "ua" => "\u{1860}\u{1838}\u{1820}",
+        "yi" => "\u{1873}",
+        "yu" => "\u{1873}\u{1823}\u{1873}",
+        "wu" => "\u{1860}",
+        "ju" => "\u{1835}\u{1873}\u{1823}\u{1873}",
+        "qu" => "\u{1834}\u{1873}\u{1823}\u{1873}",
+        "xu" => "\u{1830}\u{1873}\u{1823}\u{1873}",
+        "yuan" => "\u{1836}\u{1860}\u{1838}\u{1820}\u{1828}",
+        "juan" => "\u{1835}\u{1873}\u{1823}\u{1838}\u{1820}\u{1828}",
+        "quan" => "\u{1834}\u{1873}\u{1823}\u{1838}\u{1820}\u{1828}",
+        "xuan" => "\u{1830}\u{1873}\u{1823}\u{1838}\u{1820}\u{1828}",
+        "yue" => "\u{1836}\u{1860}\u{1838}\u{185d}",
+        "jue" => "\u{1835}\u{1873}\u{1823}\u{1838}\u{185d}",
+        "que" => "\u{1834}\u{1873}\u{1823}\u{1838}\u{185d}",
+        "xue" => "\u{1830}\u{1873}\u{1823}\u{1838}\u{185d}",
+        "yun" => "\u{1836}\u{1861}\u{1828}",
+        "jun" => "\u{1835}\u{1873}\u{1836}\u{1861}\u{1828}",
+        "qun" => "\u{1834}\u{1873}\u{1836}\u{1861}\u{1828}",
+        "xun" => "\u{1830}\u{1873}\u{1836}\u{1861}\u{1828}",
+        "n" => "\u{1828}",
+        "ng" => "\u{1829}",
+        "b" => "\u{182a}",
+        "p" => "\u{1866}",
+        "m" => "\u{182e}",
+        "f" => "\u{1876}",
+        "d" => "\u{1869}",
+        "t" => "\u{1868}",
+        "l" => "\u{182f}",
+        "g" => "\u{1864}",
+        "k" => "\u{1874}",
+        "h" => "\u{1865}",
+        "j" => "\u{1835}",
+        "q" => "\u{1834}",
+        "x" => "\u{1830}",
+        "zh" => "\u{1835}",
+        "ch" => "\u{1834}",
+        "sh" => "\u{1867}",
+        "r" => "\u{1877}",
+        "z" => "\u{186f}",
+        "c" => "\u{186e}",
+        "s" => "\u{1830}",
+        "y" => "\u{1836}",
+        "w" => "\u{1838}",
+        "zhi" => "\u{1872}",
+        "chi" => "\u{1871}",
+        "shi" => "\u{1867}\u{1873}",
+        "ri" => "\u{1877}\u{185f}",
+        "zi" => "\u{186f}\u{185f}",
+        "ci" => "\u{186e}\u{185f}",
+        "si" => "\u{1830}\u{185f}",
+        "er" => "\u{185d}\u{182f}",
+        "era" => "\u{185d}\u{1877}\u{1820}",
+        "ere" => "\u{185d}\u{1877}\u{185d}",
+        "eri" => "\u{185d}\u{1877}\u{185f}",
+        "eru" => "\u{185d}\u{1877}\u{1860}",
+        "erua" => "\u{185d}\u{1877}\u{1860}\u{1838}\u{1820}",
+        "eruo" => "\u{185d}\u{1877}\u{1823}",
+        "erou" => "\u{185d}\u{1877}\u{185d}\u{1823}",
+        "erong" => "\u{185d}\u{1877}\u{1860}\u{1829}",
+    }
+}
+
+unit_table! {
+    /// "v" for ū
+    V_PROFILE {
+        "v" => "\u{1861}",
+    }
+}
+
+unit_table! {
+    /// "uu" for ū
+    DOUBLE_U_PROFILE {
+        "uu" => "\u{1861}",
+    }
+}
+
+unit_table! {
+    /// "x" for š
+    X_PROFILE {
+        "x" => "\u{1867}",
+    }
+}
+
+unit_table! {
+    /// "sh" for š
+    SH_PROFILE {
+        "sh" => "\u{1867}",
+    }
+}
+
+unit_table! {
+    /// "''" for the apostrophe of the foreign-word letters
+    DOUBLE_APOSTROPHE_PROFILE {
+        "ts''" => "\u{186e}",
+        "k''" => "\u{183b}",
+        "g''" => "\u{186c}",
+        "h''" => "\u{186d}",
+        "c''y" => "\u{1871}",
+        "ts''y" => "\u{186e}\u{185f}",
+    }
+}
+
+unit_table! {
+    /// The apostrophe copied as a quotation mark, once the foreign-word letters take "''"
+    APOSTROPHE {
+        "'" => "'",
+    }
+}
+
+unit_table! {
+    /// ASCII punctuation copied as it is, except the apostrophe used in units like "k'" and the
+    /// braces around loanwords
+    ASCII_PUNCTUATION {
+        "!" => "!",
+        "\"" => "\"",
+        "#" => "#",
+        "$" => "$",
+        "%" => "%",
+        "&" => "&",
+        "(" => "(",
+        ")" => ")",
+        "*" => "*",
+        "+" => "+",
+        "," => ",",
+        "-" => "-",
+        "." => ".",
+        "/" => "/",
+        ":" => ":",
+        ";" => ";",
+        "<" => "<",
+        "=" => "=",
+        ">" => ">",
+        "?" => "?",
+        "@" => "@",
+        "[" => "[",
+        "\\" => "\\",
+        "]" => "]",
+        "^" => "^",
+        "_" => "_",
+        "`" => "`",
+        "|" => "|",
+        "~" => "~",
+    }
+}
+
+pub fn get_latin_manchu_map() -> &'static UnitTable {
+    &LATIN_MANCHU
+}
+
+pub fn get_latin_sibe_map() -> &'static UnitTable {
+    &LATIN_SIBE
+}
+
+pub fn get_abkai_manchu_map() -> &'static UnitTable {
+    &ABKAI_MANCHU
+}
+
+pub fn get_xinmanhan_manchu_map() -> &'static UnitTable {
+    &XINMANHAN_MANCHU
+}
+
+/// Table of a scheme, or `None` for [`Scheme::Auto`]
+pub fn get_scheme_manchu_map(scheme: Scheme) -> Option<&'static UnitTable> {
+    match scheme {
+        Scheme::Mollendorff | Scheme::MollendorffAscii => Some(get_latin_manchu_map()),
+        Scheme::Abkai => Some(get_abkai_manchu_map()),
+        Scheme::Xinmanhan => Some(get_xinmanhan_manchu_map()),
+        Scheme::Cyrillic => Some(get_cyrillic_manchu_map()),
+        Scheme::Xibe => Some(get_xibe_manchu_map()),
+        Scheme::Mongolian => Some(get_mongolian_map()),
+        Scheme::Auto => None,
+    }
+}
+
+pub fn get_cyrillic_manchu_map() -> &'static UnitTable {
+    &CYRILLIC_MANCHU
+}
+
+pub fn get_xibe_manchu_map() -> &'static UnitTable {
+    &XIBE_MANCHU
+}
+
+pub fn get_mongolian_map() -> &'static UnitTable {
+    &MONGOLIAN
+}
+
+pub fn get_pinyin_manchu_map() -> &'static UnitTable {
+    &PINYIN_MANCHU
+}
+
+/// Combining marks of the four tones of Hanyu Pinyin, which Manchu does not write
+pub fn get_pinyin_tone_marks() -> &'static [char] {
+    &['\u{300}', '\u{301}', '\u{304}', '\u{30c}']
+}
+
+pub fn get_ascii_profile_map(profile: AsciiProfile) -> &'static UnitTable {
+    match profile {
+        AsciiProfile::V => &V_PROFILE,
+        AsciiProfile::DoubleU => &DOUBLE_U_PROFILE,
+        AsciiProfile::X => &X_PROFILE,
+        AsciiProfile::Sh => &SH_PROFILE,
+        AsciiProfile::DoubleApostrophe => &DOUBLE_APOSTROPHE_PROFILE,
+    }
+}
+
+/// Table of a scheme extended with ASCII profiles, or `None` for [`Scheme::Auto`]
+///
+/// With [`AsciiProfile::DoubleApostrophe`] the units it spells with "''" are no longer read with a
+/// single apostrophe.
+pub fn get_scheme_table(scheme: Scheme, profiles: &[AsciiProfile]) -> Option<LatinManchuTable> {
+    let mut table = LatinManchuTable::new(get_scheme_manchu_map(scheme)?);
+    for profile in profiles {
+        table.extend_profile(get_ascii_profile_map(*profile));
+    }
+    if profiles.contains(&AsciiProfile::DoubleApostrophe) {
+        table.hide(
+            DOUBLE_APOSTROPHE_PROFILE
+                .units
+                .iter()
+                .map(|(unit, _)| unit.replace("''", "'")),
+        );
+    }
+    Some(table)
+}
+
+pub fn get_ascii_punctuation_map() -> &'static UnitTable {
+    &ASCII_PUNCTUATION
+}
+
+pub fn get_apostrophe_map() -> &'static UnitTable {
+    &APOSTROPHE
+}
+
+/// Inverse of `table`, taking the first unit in the table for each sequence of code points
+pub fn invert(table: &'static UnitTable) -> HashMap<&'static str, &'static str> {
+    let mut map = HashMap::new();
+    for (unit, unicode) in table.units {
+        map.entry(*unicode).or_insert(*unit);
+    }
+    map
+}
+
+pub fn get_manchu_latin_map() -> &'static HashMap<&'static str, &'static str> {
+    static MANCHU_LATIN: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    MANCHU_LATIN.get_or_init(|| invert(&LATIN_MANCHU))
+}
+
+/// Sibe letter of each Manchu letter that Sibe writes differently, matched by their unit in
+/// [`LATIN_MANCHU`] and [`LATIN_SIBE`]
+pub fn get_manchu_sibe_map() -> &'static HashMap<char, char> {
+    static MANCHU_SIBE: OnceLock<HashMap<char, char>> = OnceLock::new();
+    MANCHU_SIBE.get_or_init(|| {
+        let mut map = HashMap::new();
+        for (unit, sibe) in get_latin_sibe_map().units {
+            let Some(manchu) = (get_latin_manchu_map().lookup)(unit) else {
+                continue;
+            };
+            if manchu != *sibe {
+                map.extend(manchu.chars().zip(sibe.chars()));
+            }
+        }
+        map
+    })
+}
+
+/// Spelling of every code point in a scheme, taking ASCII units of the scheme and of its default
+/// ASCII profiles for [`Scheme::MollendorffAscii`]
+///
+/// [`Scheme::Auto`] spells like [`Scheme::Mollendorff`].
+pub fn get_scheme_latin_map(scheme: Scheme) -> &'static HashMap<&'static str, &'static str> {
+    static SCHEME_LATIN: [OnceLock<HashMap<&'static str, &'static str>>; 6] =
+        [const { OnceLock::new() }; 6];
+    let (i, table, ascii) = match scheme {
+        Scheme::Mollendorff | Scheme::Auto => return get_manchu_latin_map(),
+        Scheme::MollendorffAscii => (0, &LATIN_MANCHU, true),
+        Scheme::Abkai => (1, &ABKAI_MANCHU, false),
+        Scheme::Xinmanhan => (2, &XINMANHAN_MANCHU, false),
+        Scheme::Cyrillic => (3, &CYRILLIC_MANCHU, false),
+        Scheme::Xibe => (4, &XIBE_MANCHU, false),
+        Scheme::Mongolian => (5, &MONGOLIAN, false),
+    };
+    SCHEME_LATIN[i].get_or_init(|| {
+        let mut map = HashMap::new();
+        let profiles = scheme
+            .default_ascii_profiles()
+            .iter()
+            .map(|profile| get_ascii_profile_map(*profile));
+        let units = std::iter::once(table)
+            .chain(profiles)
+            .flat_map(|table| table.units);
+        for (unit, unicode) in units {
+            if !ascii || unit.is_ascii() {
+                map.entry(*unicode).or_insert(*unit);
+            }
+        }
+        map
+    })
+}
+
+/// Letter of the shared inventory for each sequence of code points that is one letter in the
+/// script of `scheme`
+///
+/// Manchu letters are named by their unit in [`LATIN_MANCHU`] and classical Mongolian letters as
+/// in [`MONGOLIAN_LETTERS`], so that the same letter has the same name in both scripts.
+pub fn get_letter_map(scheme: Scheme) -> &'static HashMap<&'static str, &'static str> {
+    static MONGOLIAN_LETTER: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    match scheme {
+        Scheme::Mongolian => MONGOLIAN_LETTER.get_or_init(|| invert(&MONGOLIAN_LETTERS)),
+        _ => get_manchu_latin_map(),
+    }
+}
+
+/// Letters of the shared inventory that code points in the script of `scheme` stand for, taking
+/// the longest letter first, or `None` if some code point is not part of a letter
+pub fn get_letters(scheme: Scheme, unicode: &str) -> Option<Vec<&'static str>> {
+    let letter_map = get_letter_map(scheme);
+    let mut letters = Vec::new();
+    let mut rest = unicode;
+    while !rest.is_empty() {
+        let (len, letter) = rest
+            .char_indices()
+            .map(|(i, c)| i + c.len_utf8())
+            .rev()
+            .find_map(|end| letter_map.get(&rest[..end]).map(|letter| (end, *letter)))?;
+        letters.push(letter);
+        rest = &rest[len..];
+    }
+    Some(letters)
+}
+
+/// Spelling of every letter of the shared inventory in a scheme, with the code points the
+/// spelling stands for, taking the units of [`get_scheme_latin_map`]
+pub fn get_scheme_letter_spelling_map(
+    scheme: Scheme,
+) -> &'static HashMap<&'static str, (&'static str, &'static str)> {
+    static SCHEME_LETTER: [OnceLock<HashMap<&'static str, (&'static str, &'static str)>>; 7] =
+        [const { OnceLock::new() }; 7];
+    let i = match scheme {
+        Scheme::Mollendorff | Scheme::Auto => 0,
+        Scheme::MollendorffAscii => 1,
+        Scheme::Abkai => 2,
+        Scheme::Xinmanhan => 3,
+        Scheme::Cyrillic => 4,
+        Scheme::Xibe => 5,
+        Scheme::Mongolian => 6,
+    };
+    SCHEME_LETTER[i].get_or_init(|| {
+        let letter_map = get_letter_map(scheme);
+        get_scheme_latin_map(scheme)
+            .iter()
+            .filter_map(|(unicode, unit)| {
+                let letter = letter_map.get(unicode)?;
+                Some((*letter, (*unit, *unicode)))
+            })
+            .collect()
+    })
+}
+
+/// Lower case NFC form of a unit, borrowed if the unit is in that form already
+fn fold(unit: &str) -> Cow<'_, str> {
+    let folded = unit.chars().all(|c| {
+        if c.is_ascii() {
+            !c.is_ascii_uppercase()
+        } else {
+            c.to_lowercase().eq([c])
+        }
+    });
+    if folded && (unit.is_ascii() || is_nfc_quick(unit.chars()) == IsNormalized::Yes) {
+        return Cow::Borrowed(unit);
+    }
+    Cow::Owned(unit.nfd().flat_map(char::to_lowercase).nfc().collect())
+}
+
+/// Table in a [`LatinManchuTable`], with what its units can start with and how long they are
+#[derive(Debug, Clone)]
+struct StackedTable {
+    table: &'static UnitTable,
+    /// Length in bytes of the longest unit
+    max_bytes: usize,
+    /// Bit of every ASCII character a unit starts with, in lower case
+    ascii_starts: u128,
+}
+
+impl StackedTable {
+    /// Whether a unit of the table can be `unit`, which is cheaper to check than a lookup
+    fn may_hold(&self, unit: &str) -> bool {
+        let first = unit.as_bytes()[0].to_ascii_lowercase();
+        unit.len() <= self.max_bytes && (!first.is_ascii() || self.ascii_starts >> first & 1 == 1)
+    }
+}
+
+/// Stack of static tables tokenized by longest match over their units
+///
+/// Adding a multi-letter unit only takes an entry in a table.
+/// Tables added later take precedence.
+/// Units match regardless of case and of precomposed or decomposed diacritics.
+#[derive(Debug, Clone)]
+pub struct LatinManchuTable {
+    tables: Vec<StackedTable>,
+    /// Number of graphemes in the longest unit
+    max_unit_len: usize,
+    /// Number of graphemes in the longest unit starting with each ASCII character, in lower case
+    ascii_unit_len: [u8; 128],
+    /// Bit of every ASCII character that follows each ASCII character at the start of a unit of
+    /// several graphemes, in lower case
+    ascii_pairs: [u128; 128],
+    /// Profile units that the tables before the profile read as other letters, with those letters
+    conflicts: Vec<(&'static str, String)>,
+    /// Units of the tables that are not read, in lower case NFC
+    hidden: Vec<String>,
+    /// Combining marks left out of the text before it is looked up
+    ignored: &'static [char],
+}
+
+impl LatinManchuTable {
+    pub fn new(table: &'static UnitTable) -> Self {
+        let mut latin_manchu_table = LatinManchuTable {
+            tables: Vec::new(),
+            max_unit_len: 0,
+            ascii_unit_len: [0; 128],
+            ascii_pairs: [0; 128],
+            conflicts: Vec::new(),
+            hidden: Vec::new(),
+            ignored: &[],
+        };
+        latin_manchu_table.extend(table);
+        latin_manchu_table
+    }
+
+    pub fn extend(&mut self, table: &'static UnitTable) {
+        let mut stacked = StackedTable {
+            table,
+            max_bytes: 0,
+            ascii_starts: 0,
+        };
+        for (unit, _) in table.units {
+            let len = unit.graphemes(true).count().min(MAX_UNIT_LEN);
+            self.max_unit_len = self.max_unit_len.max(len);
+            stacked.max_bytes = stacked.max_bytes.max(unit.len());
+            let first = unit.as_bytes()[0].to_ascii_lowercase();
+            if first.is_ascii() {
+                stacked.ascii_starts |= 1 << first;
+                let ascii_len = &mut self.ascii_unit_len[first as usize];
+                *ascii_len = (*ascii_len).max(len as u8);
+                let second = unit.as_bytes().get(1).map(u8::to_ascii_lowercase);
+                if let Some(second) = second.filter(|second| len > 1 && second.is_ascii()) {
+                    self.ascii_pairs[first as usize] |= 1 << second;
+                }
+            }
+        }
+        self.tables.push(stacked);
+    }
+
+    /// Extend with the table of an ASCII profile and record the units it conflicts on
+    pub fn extend_profile(&mut self, table: &'static UnitTable) {
+        for (unit, unicode) in table.units {
+            if let Some(native) = self.read(unit) {
+                if native != *unicode {
+                    self.conflicts.push((unit, native));
+                }
+            }
+        }
+        self.extend(table);
+    }
+
+    /// Stop reading `units`, such as the units a profile spells otherwise
+    pub fn hide(&mut self, units: impl IntoIterator<Item = String>) {
+        self.hidden
+            .extend(units.into_iter().map(|unit| fold(&unit).into_owned()));
+    }
+
+    /// Profile units that the tables before the profile read as other letters, with those letters
+    pub fn conflicts(&self) -> &[(&'static str, String)] {
+        &self.conflicts
+    }
+
+    /// Leave `marks` out of the text before looking it up, such as the tone marks of pinyin
+    pub fn ignore(&mut self, marks: &'static [char]) {
+        self.ignored = marks;
+    }
+
+    /// Fold `text` like [`fold`], also leaving out the ignored marks
+    fn fold_text<'b>(&self, text: &'b str) -> Cow<'b, str> {
+        if self.ignored.is_empty() || text.is_ascii() {
+            return fold(text);
+        }
+        Cow::Owned(
+            text.nfd()
+                .filter(|c| !self.ignored.contains(c))
+                .flat_map(char::to_lowercase)
+                .nfc()
+                .collect(),
+        )
+    }
+
+    /// The letters that `unit` is read as without its profile, if it is a conflicting profile unit
+    pub fn conflict(&self, unit: &str) -> Option<&str> {
+        if self.conflicts.is_empty() {
+            return None;
+        }
+        let unit = fold(unit);
+        self.conflicts
+            .iter()
+            .find(|(conflict, _)| *conflict == unit)
+            .map(|(_, native)| native.as_str())
+    }
+
+    /// Code points of every unit in `text`, or `None` if part of it cannot be converted
+    fn read(&self, text: &str) -> Option<String> {
+        let mut letters = String::new();
+        let mut i = 0;
+        while i < text.len() {
+            let (len, unicode) = self.longest_match(&text[i..])?;
+            letters.push_str(unicode);
+            i += len;
+        }
+        Some(letters)
+    }
+
+    /// Code points of `unit`, skipping the tables that cannot hold it
+    fn lookup(&self, unit: &str) -> Option<&'static str> {
+        if self.hidden.iter().any(|hidden| hidden == unit) {
+            return None;
+        }
+        self.tables
+            .iter()
+            .rev()
+            .filter(|stacked| stacked.may_hold(unit))
+            .find_map(|stacked| (stacked.table.lookup)(unit))
+    }
+
+    /// Match the longest unit at the start of `text`
+    ///
+    /// Returns the length of the unit in bytes and its code points.
+    /// Units are looked up as they are and then in lower case NFC, so "Š", "S\u{30c}" and
+    /// "s\u{30c}" all match "š". Nothing is allocated unless the text needs folding.
+    pub fn longest_match(&self, text: &str) -> Option<(usize, &'static str)> {
+        let mut ends = [0; MAX_UNIT_LEN];
+        let mut len = 0;
+        let bytes = text.as_bytes();
+        let window = bytes.len().min(self.max_unit_len + 1);
+        if bytes[..window].is_ascii() && !bytes[..window].contains(&b'\r') {
+            // Every boundary between ASCII characters other than "\r\n" is a grapheme boundary,
+            // and no unit is longer than the longest one starting with the first character. Only
+            // the first character is looked up unless the second can continue it.
+            let first = bytes.first().map_or(0, u8::to_ascii_lowercase) as usize;
+            let continued = bytes.get(1).is_some_and(|second| {
+                self.ascii_pairs[first] >> second.to_ascii_lowercase() & 1 == 1
+            });
+            let max_len = match continued {
+                true => self.ascii_unit_len[first],
+                false => self.ascii_unit_len[first].min(1),
+            };
+            len = bytes.len().min(max_len.into());
+            for (i, end) in ends[..len].iter_mut().enumerate() {
+                *end = i + 1;
+            }
+        } else {
+            for (start, grapheme) in text.grapheme_indices(true).take(self.max_unit_len) {
+                ends[len] = start + grapheme.len();
+                len += 1;
+            }
+        }
+        if len == 0 {
+            return None;
+        }
+        match self.fold_text(&text[..ends[len - 1]]) {
+            Cow::Borrowed(_) => ends[..len]
+                .iter()
+                .rev()
+                .find_map(|&end| self.lookup(&text[..end]).map(|unicode| (end, unicode))),
+            Cow::Owned(_) => {
+                // Fold each grapheme once, and match the folded text up to the end of each
+                let mut folded = String::new();
+                let mut folded_ends = [0; MAX_UNIT_LEN];
+                let mut start = 0;
+                for (end, folded_end) in ends[..len].iter().zip(&mut folded_ends) {
+                    folded.push_str(&self.fold_text(&text[start..*end]));
+                    *folded_end = folded.len();
+                    start = *end;
+                }
+                ends[..len]
+                    .iter()
+                    .zip(&folded_ends)
+                    .rev()
+                    .find_map(|(&end, &folded_end)| {
+                        self.lookup(&text[..end])
+                            .or_else(|| self.lookup(&folded[..folded_end]))
+                            .map(|unicode| (end, unicode))
+                    })
+            }
+        }
+    }
+
+    /// The shortest unit that `partial` is a strict prefix of, ignoring case and normalization
+    pub fn longer_unit(&self, partial: &str) -> Option<&'static str> {
+        let partial = &*fold(partial);
+        self.tables
+            .iter()
+            .flat_map(|stacked| stacked.table.units.iter())
+            .map(|(unit, _)| *unit)
+            .filter(|unit| unit.len() > partial.len() && unit.starts_with(partial))
+            .filter(|unit| !self.hidden.iter().any(|hidden| hidden == unit))
+            .min_by_key(|unit| (unit.len(), *unit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unit_table! {
+        EXTRA {
+            "ngg" => "\u{1820}",
+        }
+    }
+
+    #[test]
+    fn longest_match() {
+        let mut table = LatinManchuTable::new(get_latin_manchu_map());
+        assert_eq!(table.longest_match("ts'ng"), Some((3, "\u{186e}")));
+        assert_eq!(table.longest_match("s'ng"), Some((1, "\u{1830}")));
+        assert_eq!(table.longest_match("ng"), Some((2, "\u{1829}")));
+        assert_eq!(table.longest_match("u\u{304}"), Some((3, "\u{1861}")));
+        assert_eq!(table.longest_match("U\u{304}"), Some((3, "\u{1861}")));
+        assert_eq!(table.longest_match("S\u{30c}a"), Some((3, "\u{1867}")));
+        assert_eq!(table.longest_match("\u{160}a"), Some((2, "\u{1867}")));
+        assert_eq!(table.longest_match("NGa"), Some((2, "\u{1829}")));
+        assert_eq!(table.longest_match("C'Y"), Some((3, "\u{1871}")));
+        assert_eq!(table.longest_match("ng\u{304}"), Some((1, "\u{1828}")));
+        assert_eq!(table.longest_match("k'\r\n"), Some((2, "\u{183b}")));
+        assert_eq!(table.longest_match("TS'Ya"), Some((4, "\u{186e}\u{185f}")));
+        assert_eq!(table.longest_match("niyalma"), Some((1, "\u{1828}")));
+        assert_eq!(table.longest_match("qa"), None);
+        assert_eq!(table.longest_match(""), None);
+
+        table.extend(&EXTRA);
+        assert_eq!(table.longest_match("ng"), Some((2, "\u{1829}")));
+        assert_eq!(table.longest_match("ngga"), Some((3, "\u{1820}")));
+        table.extend(get_ascii_profile_map(AsciiProfile::DoubleU));
+        assert_eq!(table.longest_match("Uun"), Some((2, "\u{1861}")));
+
+        assert_eq!(table.longer_unit("c'"), Some("c'y"));
+        assert_eq!(table.longer_unit("ts"), Some("ts'"));
+        assert_eq!(table.longer_unit("a"), None);
+    }
+
+    /// Check that `table` spells every Möllendorff unit, given as (spelling, Möllendorff) pairs
+    fn assert_alphabet(table: &UnitTable, alphabet: &[(&str, &str)]) {
+        assert_eq!(alphabet.len(), table.units.len());
+        for (spelling, mollendorff) in alphabet {
+            let unicode = (LATIN_MANCHU.lookup)(mollendorff);
+            assert!(unicode.is_some(), "{}", mollendorff);
+            assert_eq!((table.lookup)(spelling), unicode, "{}", spelling);
+        }
+    }
+
+    #[test]
+    fn abkai_alphabet() {
+        let alphabet = [
+            ("a", "a"),
+            ("e", "e"),
+            ("i", "i"),
+            ("o", "o"),
+            ("u", "u"),
+            ("uu", "\u{16b}"),
+            ("n", "n"),
+            ("ng", "ng"),
+            ("b", "b"),
+            ("p", "p"),
+            ("s", "s"),
+            ("x", "\u{161}"),
+            ("k", "k"),
+            ("g", "g"),
+            ("h", "h"),
+            ("l", "l"),
+            ("m", "m"),
+            ("t", "t"),
+            ("d", "d"),
+            ("r", "r"),
+            ("j", "j"),
+            ("y", "y"),
+            ("c", "c"),
+            ("f", "f"),
+            ("w", "w"),
+            ("ts", "ts'"),
+            ("z", "dz"),
+            ("kk", "k'"),
+            ("gg", "g'"),
+            ("hh", "h'"),
+            ("ch", "c'y"),
+            (",", ","),
+            (".", "."),
+        ];
+        assert_alphabet(&ABKAI_MANCHU, &alphabet);
+    }
+
+    #[test]
+    fn xinmanhan_alphabet() {
+        let alphabet = [
+            ("a", "a"),
+            ("e", "e"),
+            ("i", "i"),
+            ("o", "o"),
+            ("u", "u"),
+            ("v", "\u{16b}"),
+            ("n", "n"),
+            ("ng", "ng"),
+            ("b", "b"),
+            ("p", "p"),
+            ("s", "s"),
+            ("x", "\u{161}"),
+            ("k", "k"),
+            ("g", "g"),
+            ("h", "h"),
+            ("l", "l"),
+            ("m", "m"),
+            ("t", "t"),
+            ("d", "d"),
+            ("r", "r"),
+            ("j", "j"),
+            ("y", "y"),
+            ("q", "c"),
+            ("f", "f"),
+            ("w", "w"),
+            ("c", "ts'"),
+            ("z", "dz"),
+            ("kk", "k'"),
+            ("gg", "g'"),
+            ("hh", "h'"),
+            ("ch", "c'y"),
+            (",", ","),
+            (".", "."),
+        ];
+        assert_alphabet(&XINMANHAN_MANCHU, &alphabet);
+    }
+
+    #[test]
+    fn cyrillic_alphabet() {
+        let alphabet = [
+            ("а", "a"),
+            ("э", "e"),
+            ("е", "e"),
+            ("и", "i"),
+            ("о", "o"),
+            ("у", "u"),
+            ("ӯ", "\u{16b}"),
+            ("н", "n"),
+            ("нг", "ng"),
+            ("б", "b"),
+            ("п", "p"),
+            ("с", "s"),
+            ("ш", "\u{161}"),
+            ("к", "k"),
+            ("г", "g"),
+            ("х", "h"),
+            ("л", "l"),
+            ("м", "m"),
+            ("т", "t"),
+            ("д", "d"),
+            ("р", "r"),
+            ("дж", "j"),
+            ("й", "y"),
+            ("ч", "c"),
+            ("ф", "f"),
+            ("в", "w"),
+            ("ц", "ts'"),
+            ("дз", "dz"),
+            ("к'", "k'"),
+            ("г'", "g'"),
+            ("х'", "h'"),
+            ("чы", "c'y"),
+            (",", ","),
+            (".", "."),
+        ];
+        assert_alphabet(&CYRILLIC_MANCHU, &alphabet);
+    }
+
+    #[test]
+    fn xibe_alphabet() {
+        let alphabet = [
+            ("a", "a"),
+            ("e", "e"),
+            ("i", "i"),
+            ("o", "o"),
+            ("u", "u"),
+            ("v", "\u{16b}"),
+            ("n", "n"),
+            ("ng", "ng"),
+            ("b", "b"),
+            ("p", "p"),
+            ("s", "s"),
+            ("x", "\u{161}"),
+            ("k", "k"),
+            ("g", "g"),
+            ("h", "h"),
+            ("l", "l"),
+            ("m", "m"),
+            ("t", "t"),
+            ("d", "d"),
+            ("r", "r"),
+            ("zh", "j"),
+            ("y", "y"),
+            ("ch", "c"),
+            ("f", "f"),
+            ("w", "w"),
+            ("c", "ts'"),
+            ("z", "dz"),
+            ("kk", "k'"),
+            ("gg", "g'"),
+            ("hh", "h'"),
+            ("chy", "c'y"),
+            (",", ","),
+            (".", "."),
+        ];
+        assert_alphabet(&XIBE_MANCHU, &alphabet);
+    }
+
+    #[test]
+    fn mongolian_letters() {
+        for letter in '\u{1820}'..='\u{1842}' {
+            let letter = letter.to_string();
+            assert!(
+                MONGOLIAN
+                    .units
+                    .iter()
+                    .any(|(_, unicode)| *unicode == letter),
+                "{}",
+                letter
+            );
+        }
+        let table = LatinManchuTable::new(get_mongolian_map());
+        assert_eq!(table.longest_match("Γal"), Some((2, "\u{182d}")));
+        assert_eq!(table.longest_match("ngu"), Some((2, "\u{1829}")));
+        assert_eq!(table.longest_match("zhi"), Some((3, "\u{1841}")));
+        assert_eq!(table.longest_match("zha"), Some((1, "\u{183d}")));
+    }
+
+    #[test]
+    fn shared_letters() {
+        for scheme in [
+            Scheme::Mollendorff,
+            Scheme::Abkai,
+            Scheme::Xinmanhan,
+            Scheme::Cyrillic,
+            Scheme::Xibe,
+            Scheme::Mongolian,
+        ] {
+            let table = get_scheme_manchu_map(scheme).unwrap();
+            for (unit, unicode) in table.units {
+                assert!(get_letters(scheme, unicode).is_some(), "{}", unit);
+            }
+        }
+        for (_, unicode) in PINYIN_MANCHU.units {
+            assert!(get_letters(Scheme::Mollendorff, unicode).is_some());
+        }
+        assert_eq!(
+            get_letters(
+                Scheme::Mongolian,
+                "\u{182c}\u{1820}\u{182d}\u{1820}\u{1828}"
+            ),
+            get_letters(
+                Scheme::Mollendorff,
+                "\u{1874}\u{1820}\u{1864}\u{1820}\u{1828}"
+            )
+        );
+        assert_eq!(
+            get_letters(Scheme::Mollendorff, "\u{1830}\u{185f}\u{1830}"),
+            Some(vec!["sy", "s"])
+        );
+        assert_eq!(get_letters(Scheme::Mongolian, "\u{1874}"), None);
+        let spelling = get_scheme_letter_spelling_map(Scheme::Mongolian);
+        assert_eq!(spelling.get("jy"), Some(&("zhi", "\u{1841}")));
+        assert_eq!(spelling.get("\u{16b}"), None);
+        let spelling = get_scheme_letter_spelling_map(Scheme::Abkai);
+        assert_eq!(spelling.get("\u{161}"), Some(&("x", "\u{1867}")));
+    }
+
+    #[test]
+    fn foreign_letters() {
+        // MANCHU I to MANCHU ZHA, SIBE IY, CHA and ZHA, and the MANCHU ALI GALI letters
+        let letters = ('\u{1873}'..='\u{1877}')
+            .chain(['\u{185f}', '\u{1871}', '\u{1872}'])
+            .chain('\u{189a}'..='\u{18a5}')
+            .chain(['\u{18a8}', '\u{18aa}']);
+        for letter in letters {
+            let (unit, unicode) = LATIN_MANCHU
+                .units
+                .iter()
+                .find(|(_, unicode)| unicode.contains(letter))
+                .unwrap_or_else(|| panic!("U+{:04X}", letter as u32));
+            assert_eq!(get_manchu_latin_map().get(unicode), Some(unit));
+        }
+        let table = LatinManchuTable::new(get_latin_manchu_map());
+        assert_eq!(table.longest_match("žyi"), Some((3, "\u{1877}\u{185f}")));
+        assert_eq!(table.longest_match("ts'yi"), Some((4, "\u{186e}\u{185f}")));
+        assert_eq!(table.longest_match("Ṭa"), Some((3, "\u{189e}")));
+        assert_eq!(table.longest_match("J\u{30c}a"), Some((3, "\u{189d}")));
+    }
+
+    #[test]
+    fn sibe_letters() {
+        for (unit, _) in LATIN_SIBE.units {
+            assert!((LATIN_MANCHU.lookup)(unit).is_some(), "{}", unit);
+        }
+        let sibe = get_manchu_sibe_map();
+        assert_eq!(sibe.get(&'\u{1873}'), Some(&'\u{185e}'));
+        assert_eq!(sibe.get(&'\u{1877}'), Some(&'\u{1872}'));
+        assert_eq!(sibe.get(&'\u{1820}'), None);
+    }
+
+    #[test]
+    fn lookup_matches_units() {
+        for table in [
+            &LATIN_MANCHU,
+            &LATIN_SIBE,
+            &ABKAI_MANCHU,
+            &XINMANHAN_MANCHU,
+            &CYRILLIC_MANCHU,
+            &XIBE_MANCHU,
+            &MONGOLIAN,
+            &MONGOLIAN_LETTERS,
+            &PINYIN_MANCHU,
+            &ASCII_PUNCTUATION,
+        ] {
+            for (unit, unicode) in table.units {
+                assert_eq!((table.lookup)(unit), Some(*unicode));
+            }
+        }
+    }
 }
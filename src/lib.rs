@@ -1,7 +1,27 @@
-pub use crate::converter::ManchuConverter;
+pub use crate::converter::{
+    Conversion, Converter, ConverterBuilder, ManchuConverter, MongolianConverter,
+};
+pub use crate::detect::{detect_scheme, Detection};
+pub use crate::error::{ConversionError, Span, StreamError};
+pub use crate::latin_converter::LatinConverter;
+pub use crate::options::{
+    AsciiProfile, ErrorPolicy, Normalization, ProfileConflict, PunctuationPolicy, Scheme, Target,
+    WhitespacePolicy,
+};
+pub use crate::token::{Token, TokenKind, Tokens};
 
 mod converter;
+mod detect;
+mod error;
+mod hangul;
+mod ipa;
+mod katakana;
+mod latin_converter;
 mod latin_manchu_unicode_mapper;
+mod options;
+mod romanize;
+mod stream;
+mod token;
 
 #[cfg(test)]
 mod tests {
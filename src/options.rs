@@ -0,0 +1,213 @@
+use unicode_normalization::UnicodeNormalization;
+
+use crate::hangul::write_hangul;
+use crate::ipa::ipa_segment;
+use crate::katakana::kana;
+use crate::latin_manchu_unicode_mapper::get_manchu_sibe_map;
+
+/// Romanization scheme of the input text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Scheme {
+    /// Möllendorff transliteration with the diacritics of ū and š, also accepting "v" and "x" for them
+    #[default]
+    Mollendorff,
+    /// Möllendorff transliteration typed in ASCII, with "v" for ū and "x" for š
+    MollendorffAscii,
+    /// Abkai romanization, with "uu" for ū, "x" for š and "z" for dz
+    Abkai,
+    /// Xinmanhan romanization, with "v" for ū, "x" for š, "q" for c and "c" for ts'
+    Xinmanhan,
+    /// Zakharov-style Cyrillic transcription, such as "такӯрафи" for takūrafi
+    Cyrillic,
+    /// Xibe romanization of modern Sibe, with "x" for š, "zh" and "ch" for j and c, and "z" and
+    /// "c" for dz and ts', written in Sibe orthography with [`Target::Sibe`]
+    Xibe,
+    /// Classical Mongolian transliteration, such as "mongγol" for ᠮᠣᠩᠭᠣᠯ, written in Mongolian
+    /// letters rather than Manchu ones
+    Mongolian,
+    /// Detect the scheme of each line with [`detect_scheme`](crate::detect_scheme)
+    Auto,
+}
+
+impl Scheme {
+    /// ASCII profiles a scheme accepts unless others are chosen
+    pub fn default_ascii_profiles(&self) -> &'static [AsciiProfile] {
+        match self {
+            Scheme::Mollendorff | Scheme::MollendorffAscii => &[AsciiProfile::V, AsciiProfile::X],
+            _ => &[],
+        }
+    }
+
+    /// Whether the scheme writes Manchu Script, whose texts mark Chinese loanwords with "{" and "}"
+    pub(crate) fn writes_manchu(&self) -> bool {
+        !matches!(self, Scheme::Mongolian)
+    }
+}
+
+/// ASCII fallback for letters with diacritics and apostrophes, added to the units of a scheme
+///
+/// Profiles can be combined. A profile unit that the scheme already reads as other letters,
+/// such as "sh" for "s" and "h", is a [`ProfileConflict`] and words containing it are warned about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AsciiProfile {
+    /// "v" for ū
+    V,
+    /// "uu" for ū
+    DoubleU,
+    /// "x" for š
+    X,
+    /// "sh" for š
+    Sh,
+    /// "''" for the apostrophe of ts', k', g', h', c'y and ts'y, where a single one is taken as a
+    /// quotation mark and copied with [`PunctuationPolicy::Keep`]
+    DoubleApostrophe,
+}
+
+/// A unit of an ASCII profile that a scheme reads as other letters without the profile
+///
+/// With the profile the unit takes precedence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileConflict {
+    pub scheme: Scheme,
+    /// The profile unit, such as "sh"
+    pub unit: &'static str,
+    /// Manchu Script the scheme reads the unit as without the profile, such as "ᠰᡥ" for "sh"
+    pub native: String,
+}
+
+/// What transcripted texts are converted to
+///
+/// Classical Mongolian is only written in its script, and its words are
+/// [`ConversionError::TargetMismatch`](crate::ConversionError::TargetMismatch) errors with the
+/// other targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Target {
+    /// Manchu Script
+    #[default]
+    Script,
+    /// Reconstructed pronunciation in IPA, one segment for each letter
+    Ipa,
+    /// Katakana reading, one kana syllable for each consonant and the vowel after it
+    Katakana,
+    /// Hangul reading in the style of Qing-era Korean textbooks, one syllable block for each vowel
+    Hangul,
+    /// Script in the orthography of modern Sibe, with the SIBE letters where Sibe writes a letter
+    /// differently from Manchu
+    Sibe,
+}
+
+impl Target {
+    /// Write what the target writes for a letter of Manchu Script, given the letter before it and
+    /// the letters after it in the word
+    ///
+    /// Returns `false` if the target has nothing for the code point, which is then written as it is.
+    pub(crate) fn write(
+        &self,
+        letter: char,
+        previous: Option<char>,
+        following: &[char],
+        convert_result: &mut String,
+    ) -> bool {
+        let segment = match self {
+            Target::Script => None,
+            Target::Ipa => ipa_segment(letter, following.first().copied()),
+            Target::Katakana => kana(letter, previous, following.first().copied()),
+            Target::Hangul => return write_hangul(letter, previous, following, convert_result),
+            Target::Sibe => {
+                return get_manchu_sibe_map()
+                    .get(&letter)
+                    .map(|sibe| convert_result.push(*sibe))
+                    .is_some()
+            }
+        };
+        segment
+            .map(|segment| convert_result.push_str(segment))
+            .is_some()
+    }
+
+    /// Write the Manchu Script of converted text in the target, copying code points that are not
+    /// letters as they are
+    pub(crate) fn render(&self, script: &str, convert_result: &mut String) {
+        let letters = script.chars().collect::<Vec<_>>();
+        for (i, letter) in letters.iter().enumerate() {
+            let previous = i.checked_sub(1).map(|i| letters[i]);
+            if !self.write(*letter, previous, &letters[i + 1..], convert_result) {
+                convert_result.push(*letter);
+            }
+        }
+    }
+}
+
+/// What to do when a grapheme cannot be converted
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum ErrorPolicy {
+    /// Stop and return the error
+    #[default]
+    Abort,
+    /// Copy the grapheme as it is and continue
+    PassThrough,
+    /// Write the marker instead of the grapheme and continue
+    Replace(String),
+    /// Drop the grapheme and continue
+    Skip,
+}
+
+impl ErrorPolicy {
+    /// Replace graphemes that cannot be converted with U+FFFD REPLACEMENT CHARACTER
+    pub fn replacement_character() -> Self {
+        ErrorPolicy::Replace('\u{fffd}'.to_string())
+    }
+}
+
+/// How whitespace between words and lines is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum WhitespacePolicy {
+    /// Separate words with a single space and lines with "\n"
+    #[default]
+    Collapse,
+    /// Copy every whitespace character, including line endings, as it is
+    Preserve,
+}
+
+/// How punctuation is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum PunctuationPolicy {
+    /// Write "," and "." as Manchu comma and full stop
+    #[default]
+    Convert,
+    /// Copy ASCII punctuation as it is
+    Keep,
+}
+
+/// Unicode normalization applied to the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Normalization {
+    #[default]
+    None,
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+impl Normalization {
+    /// Normalize `text` from the byte offset `start`
+    pub(crate) fn apply(&self, text: &mut String, start: usize) {
+        let normalized: String = match self {
+            Normalization::None => return,
+            Normalization::Nfc => text[start..].nfc().collect(),
+            Normalization::Nfd => text[start..].nfd().collect(),
+            Normalization::Nfkc => text[start..].nfkc().collect(),
+            Normalization::Nfkd => text[start..].nfkd().collect(),
+        };
+        text.truncate(start);
+        text.push_str(&normalized);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::converter::{read_table, Conversion, Converter};
+use crate::error::{ConversionError, Locator};
+use crate::latin_converter::Spelling;
+use crate::latin_manchu_unicode_mapper::{
+    get_ascii_punctuation_map, get_latin_manchu_map, get_letters, get_scheme_letter_spelling_map,
+    LatinManchuTable,
+};
+use crate::options::{ErrorPolicy, Scheme, Target};
+use crate::token::{Token, TokenKind, Tokens};
+
+impl Converter {
+    /// Spell transcripted texts in the `target` scheme
+    ///
+    /// The text is split into the same tokens as [`Converter::tokens`], and each letter is spelled
+    /// as the target scheme spells it. Whitespace is copied as it is.
+    /// Letters without a spelling in the target scheme are errors, and so are spellings that read
+    /// back as other letters, such as "t" and "s" as "ts" in Abkai.
+    /// A `target` of [`Scheme::Auto`] spells like [`Scheme::Mollendorff`].
+    /// Letters are matched across scripts by their name in the shared letter inventory, so a
+    /// classical Mongolian word is spelled in a Manchu scheme letter by letter, and letters that
+    /// only one script has, such as ö and ū, are errors in the other.
+    ///
+    /// Returns the first error if the error policy is [`ErrorPolicy::Abort`], otherwise errors are recovered from.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use manchu_converter::{Converter, Scheme};
+    ///
+    /// fn main() {
+    ///     let converter = Converter::builder().scheme(Scheme::Abkai).build();
+    ///     let result = converter.romanize("takuurafi xolo", Scheme::Mollendorff).unwrap();
+    ///     assert_eq!(result, "takūrafi šolo");
+    ///     let result = converter.romanize("takuurafi xolo", Scheme::Xinmanhan).unwrap();
+    ///     assert_eq!(result, "takvrafi xolo");
+    ///     let result = converter.romanize("takuurafi xolo", Scheme::Cyrillic).unwrap();
+    ///     assert_eq!(result, "такӯрафи шоло");
+    /// }
+    /// ```
+    pub fn romanize(&self, text: &str, target: Scheme) -> Result<String, ConversionError> {
+        let result = self.romanize_with_diagnostics(text, target);
+        match result.errors.into_iter().next() {
+            Some(error) if *self.error_policy() == ErrorPolicy::Abort => Err(error),
+            _ => Ok(result.output),
+        }
+    }
+
+    /// Spell transcripted texts in the `target` scheme like [`Converter::romanize`] and collect every error
+    ///
+    /// With [`ErrorPolicy::Abort`] the units that cannot be spelled are left as they are.
+    pub fn romanize_with_diagnostics(&self, text: &str, target: Scheme) -> Conversion {
+        // Spellings are read back the way the converter reads the target
+        let scheme = match target {
+            Scheme::Auto => Scheme::Mollendorff,
+            target => target,
+        };
+        let table = read_table(scheme, scheme.default_ascii_profiles(), self.punctuation())
+            .unwrap_or_else(|| LatinManchuTable::new(get_latin_manchu_map()));
+
+        let tables = self.tables(text, None, false);
+        let tokens = Tokens::new(self, text, Target::Script, tables.clone()).collect::<Vec<_>>();
+        let mut locator = Locator::new(text);
+        let mut errors = Vec::new();
+        let mut output = String::with_capacity(text.len());
+        let mut i = 0;
+        while i < tokens.len() {
+            if tokens[i].kind == TokenKind::Whitespace {
+                output.push_str(tokens[i].unit);
+                i += 1;
+                continue;
+            }
+            let end = tokens[i..]
+                .iter()
+                .position(|token| token.kind == TokenKind::Whitespace)
+                .map_or(tokens.len(), |position| i + position);
+            let (source, _) = tables.at(tokens[i].input.start);
+            errors.extend(romanize_word(
+                &mut locator,
+                &tokens[i..end],
+                source,
+                target,
+                &table,
+                self.error_policy(),
+                &mut output,
+            ));
+            i = end;
+        }
+        Conversion {
+            output,
+            errors,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// Spell the tokens of a word in `target`, append them to `convert_result` and return the errors
+fn romanize_word(
+    locator: &mut Locator,
+    tokens: &[Token],
+    source: Scheme,
+    target: Scheme,
+    table: &LatinManchuTable,
+    error_policy: &ErrorPolicy,
+    convert_result: &mut String,
+) -> Vec<ConversionError> {
+    let offset = tokens[0].input.start;
+    let word = &locator.text()[offset..tokens[tokens.len() - 1].input.end];
+    let mut word_errors = Vec::new();
+    let mut spelled = Spelling::with_capacity(word.len());
+    for token in tokens {
+        if token.kind == TokenKind::Unknown {
+            word_errors.push(ConversionError::UnknownGrapheme {
+                word: word.to_string(),
+                grapheme: token.unit.to_string(),
+                span: locator.locate(token.input.start, token.input.end),
+            });
+            convert_result.push_str(&token.emitted);
+            continue;
+        }
+        let unicode = token.emitted.as_str();
+        if unicode.is_empty() {
+            continue;
+        }
+        match spelling(source, target, unicode) {
+            Some(spelling) => {
+                let input = token.input.start - offset..token.input.end - offset;
+                for (unit, letter) in spelling {
+                    spelled.push(letter, input.clone(), unit);
+                    convert_result.push_str(unit);
+                }
+            }
+            None => {
+                word_errors.push(ConversionError::Unrepresentable {
+                    word: word.to_string(),
+                    grapheme: token.unit.to_string(),
+                    scheme: target,
+                    span: locator.locate(token.input.start, token.input.end),
+                });
+                match error_policy {
+                    ErrorPolicy::Abort | ErrorPolicy::PassThrough => {
+                        convert_result.push_str(token.unit)
+                    }
+                    ErrorPolicy::Replace(marker) => convert_result.push_str(marker),
+                    ErrorPolicy::Skip => {}
+                }
+            }
+        }
+    }
+    spelled.check(locator, offset, word, table, &mut word_errors);
+    word_errors.sort_by_key(|error| error.span().start);
+    word_errors
+}
+
+/// Spelling in `target` of each letter of the shared inventory that the code points of a token
+/// in `source` stand for, with the code points that the spelling stands for in `target`
+///
+/// Kept punctuation is spelled as itself.
+fn spelling(
+    source: Scheme,
+    target: Scheme,
+    unicode: &str,
+) -> Option<Vec<(&'static str, &'static str)>> {
+    if let Some(letters) = get_letters(source, unicode) {
+        let spelling_map = get_scheme_letter_spelling_map(target);
+        return letters
+            .iter()
+            .map(|letter| spelling_map.get(letter).copied())
+            .collect();
+    }
+    get_ascii_punctuation_map()
+        .units
+        .iter()
+        .find(|(_, punctuation)| *punctuation == unicode)
+        .map(|(unit, punctuation)| vec![(*unit, *punctuation)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Span;
+    use crate::latin_manchu_unicode_mapper::get_mongolian_map;
+    use crate::options::PunctuationPolicy;
+
+    #[test]
+    fn romanize() {
+        let converter = Converter::builder()
+            .scheme(Scheme::Auto)
+            .punctuation(PunctuationPolicy::Keep)
+            .build();
+        let texts = [
+            (Scheme::Mollendorff, "cooha be takūrafi, c'y ts'!"),
+            (Scheme::MollendorffAscii, "cooha be takvrafi, c'y ts'!"),
+            (Scheme::Abkai, "cooha be takuurafi, ch ts!"),
+            (Scheme::Xinmanhan, "qooha be takvrafi, ch c!"),
+            (Scheme::Cyrillic, "чооха бэ такӯрафи, чы ц!"),
+            (Scheme::Xibe, "chooha be takvrafi, chy c!"),
+        ];
+        for (source, text) in texts {
+            for (target, expected) in texts {
+                let converter = Converter::builder()
+                    .scheme(source)
+                    .punctuation(PunctuationPolicy::Keep)
+                    .build();
+                assert_eq!(converter.romanize(text, target).unwrap(), expected);
+            }
+        }
+        assert_eq!(
+            converter
+                .romanize("qooha be\n  takvrafi", Scheme::Mollendorff)
+                .unwrap(),
+            "cooha be\n  takūrafi"
+        );
+
+        // The letters of a scheme that are also ASCII punctuation, read as the scheme reads them
+        // unless punctuation is kept
+        let converter = Converter::builder().scheme(Scheme::Mongolian).build();
+        let text = "mongγol-un bičiγ, qaγan.";
+        assert_eq!(converter.romanize(text, Scheme::Mongolian).unwrap(), text);
+        let converter = Converter::new();
+        assert_eq!(
+            converter.romanize("cooha, be.", Scheme::Abkai).unwrap(),
+            "cooha, be."
+        );
+    }
+
+    #[test]
+    fn romanize_errors() {
+        let converter = Converter::new();
+        let result = converter.romanize_with_diagnostics("tsa Qa", Scheme::Abkai);
+        assert_eq!(result.output, "tsa Qa");
+        let errors = result
+            .errors
+            .iter()
+            .map(|error| (error.grapheme(), error.span().start))
+            .collect::<Vec<_>>();
+        assert_eq!(errors, vec![("ts", 0), ("Q", 4)]);
+        assert!(matches!(
+            result.errors[0],
+            ConversionError::AmbiguousSpelling { .. }
+        ));
+        assert_eq!(
+            converter.romanize("tsa", Scheme::Abkai).unwrap_err(),
+            result.errors[0]
+        );
+
+        // Letters are matched across scripts, and letters that one script lacks are errors
+        let auto = Converter::builder()
+            .scheme(Scheme::Auto)
+            .error_policy(ErrorPolicy::Skip)
+            .build();
+        let result = auto.romanize_with_diagnostics("manju gisun\nmongγol kele", Scheme::Abkai);
+        assert_eq!(result.output, "manju gisun\nmonggol kele");
+        assert!(result.errors.is_empty());
+        let result =
+            auto.romanize_with_diagnostics("manju gisun\nmongγol ügülel", Scheme::Xinmanhan);
+        assert_eq!(result.output, "manju gisun\nmonggol glel");
+        let graphemes = result
+            .errors
+            .iter()
+            .map(ConversionError::grapheme)
+            .collect::<Vec<_>>();
+        assert_eq!(graphemes, vec!["ü", "ü"]);
+        assert_eq!(
+            converter.romanize("manju", Scheme::Mongolian).unwrap(),
+            "manǰu"
+        );
+
+        // A target without a spelling for ū
+        let table = LatinManchuTable::new(get_mongolian_map());
+        let tokens = converter.tokens("tūta").collect::<Vec<_>>();
+        let mut output = String::new();
+        let errors = romanize_word(
+            &mut Locator::new("tūta"),
+            &tokens,
+            Scheme::Mollendorff,
+            Scheme::Mongolian,
+            &table,
+            &ErrorPolicy::replacement_character(),
+            &mut output,
+        );
+        assert_eq!(output, "t\u{fffd}ta");
+        assert_eq!(
+            errors,
+            vec![ConversionError::Unrepresentable {
+                word: "tūta".to_string(),
+                grapheme: "ū".to_string(),
+                scheme: Scheme::Mongolian,
+                span: Span {
+                    line: 1,
+                    column: 2,
+                    start: 1,
+                    end: 3
+                }
+            }]
+        );
+    }
+}
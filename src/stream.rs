@@ -0,0 +1,303 @@
+use std::io::{self, BufRead, Write};
+use std::str;
+
+use crate::converter::{Converter, Loanword};
+use crate::error::{ConversionError, StreamError};
+use crate::options::{ErrorPolicy, WhitespacePolicy};
+
+/// Number of bytes of a line read before the line is cut at whitespace and converted in parts
+const PART_LIMIT: usize = 1 << 16;
+
+impl Converter {
+    /// Convert transcripted texts read from `reader` and write Manchu Script to `writer` line by line
+    ///
+    /// Only one line is held in memory at a time, and a line longer than 64 KiB is cut at whitespace
+    /// and converted in parts, so graphemes and units such as "c'y" are never split however the
+    /// reader buffers the input. Only a single word longer than that is held whole.
+    /// The output is the same as [`Converter::convert`] on the whole input, also with
+    /// [`Scheme::Auto`](crate::Scheme::Auto), except that it detects the scheme of each part of a
+    /// long line.
+    /// With [`ErrorPolicy::Abort`] the lines before the first error are written and the error is returned.
+    /// A "{" that is never closed is only found at the end of the input, after every line is written.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use manchu_converter::Converter;
+    ///
+    /// fn main() {
+    ///     let mut output = Vec::new();
+    ///     Converter::new()
+    ///         .convert_stream("cooha be\nacaha\n".as_bytes(), &mut output)
+    ///         .unwrap();
+    ///     assert_eq!(String::from_utf8(output).unwrap(), "ᠴᠣᠣᡥᠠ ᠪᡝ\nᠠᠴᠠᡥᠠ")
+    /// }
+    /// ```
+    pub fn convert_stream<R: BufRead, W: Write>(
+        &self,
+        reader: R,
+        writer: W,
+    ) -> Result<(), StreamError> {
+        let mut errors = Vec::new();
+        self.stream(reader, writer, &mut errors, true, PART_LIMIT)?;
+        match errors.into_iter().next() {
+            Some(error) if *self.error_policy() == ErrorPolicy::Abort => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Convert a stream like [`Converter::convert_stream`] and collect every error instead of stopping at the first one
+    pub fn convert_stream_with_diagnostics<R: BufRead, W: Write>(
+        &self,
+        reader: R,
+        writer: W,
+    ) -> io::Result<Vec<ConversionError>> {
+        let mut errors = Vec::new();
+        self.stream(reader, writer, &mut errors, false, PART_LIMIT)?;
+        Ok(errors)
+    }
+
+    fn stream<R: BufRead, W: Write>(
+        &self,
+        mut reader: R,
+        mut writer: W,
+        errors: &mut Vec<ConversionError>,
+        stop_on_error: bool,
+        limit: usize,
+    ) -> io::Result<()> {
+        // Bytes read and not converted yet
+        let mut buffer = Vec::new();
+        let mut convert_result = String::new();
+        let mut line_errors = Vec::new();
+        // Bytes and lines read before the current part, and characters of its line before it
+        let mut offset = 0;
+        let mut line_number = 0;
+        let mut column = 0;
+        // Whether words of the current line are written already
+        let mut line_has_words = false;
+        // Loanword markup the current part starts in
+        let mut loanword = Loanword::default();
+        // Scheme of the part before, for parts without any telltale unit
+        let mut scheme = None;
+        // Bytes to read before cutting a long line
+        let mut part_limit = limit;
+        loop {
+            let at_limit = read_part(&mut reader, &mut buffer, part_limit)?;
+            if buffer.is_empty() {
+                break;
+            }
+            let (part, ends_line) = if at_limit {
+                let valid = match str::from_utf8(&buffer) {
+                    Ok(valid) => valid,
+                    Err(error) if error.error_len().is_none() => {
+                        str::from_utf8(&buffer[..error.valid_up_to()]).unwrap_or_default()
+                    }
+                    Err(error) => return Err(io::Error::new(io::ErrorKind::InvalidData, error)),
+                };
+                // Cut at the last whitespace so that no word is split, or read on
+                match valid.rfind(char::is_whitespace).filter(|cut| *cut > 0) {
+                    Some(cut) => (&valid[..cut], false),
+                    None => {
+                        part_limit += limit;
+                        continue;
+                    }
+                }
+            } else {
+                let line = str::from_utf8(&buffer)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+                (line, true)
+            };
+            part_limit = limit;
+            convert_result.clear();
+            let text = match self.whitespace() {
+                WhitespacePolicy::Collapse => {
+                    let text = if ends_line {
+                        part.strip_suffix('\n')
+                            .map_or(part, |line| line.strip_suffix('\r').unwrap_or(line))
+                    } else {
+                        part
+                    };
+                    let has_words = !text.trim().is_empty();
+                    if column == 0 && line_number != 0 {
+                        convert_result.push('\n');
+                    } else if line_has_words && has_words {
+                        convert_result.push(' ');
+                    }
+                    line_has_words |= has_words;
+                    text
+                }
+                WhitespacePolicy::Preserve => part,
+            };
+            // A "{" opened on an earlier part is already located in the whole input
+            let unclosed = loanword.unclosed.take();
+            let tables = self.tables(text, scheme, loanword.open);
+            scheme = Some(tables.last());
+            self.convert_lines(
+                text,
+                &tables,
+                &mut loanword,
+                &mut line_errors,
+                &mut convert_result,
+            );
+            match &mut loanword.unclosed {
+                Some(error) => error.shift(offset, line_number, column),
+                None if loanword.open => loanword.unclosed = unclosed,
+                None => {}
+            }
+            for mut error in line_errors.drain(..) {
+                error.shift(offset, line_number, column);
+                errors.push(error);
+            }
+            if stop_on_error && *self.error_policy() == ErrorPolicy::Abort && !errors.is_empty() {
+                break;
+            }
+            writer.write_all(convert_result.as_bytes())?;
+            offset += part.len();
+            if ends_line {
+                line_number += 1;
+                column = 0;
+                line_has_words = false;
+            } else {
+                column += part.chars().count();
+            }
+            let len = part.len();
+            buffer.drain(..len);
+        }
+        loanword.finish(errors);
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Read from `reader` onto `buffer` up to the end of the line, unless `buffer` reaches `limit`
+/// bytes first
+///
+/// Returns whether `buffer` reached the limit without holding the end of the line.
+fn read_part<R: BufRead>(reader: &mut R, buffer: &mut Vec<u8>, limit: usize) -> io::Result<bool> {
+    while buffer.len() < limit {
+        let available = match reader.fill_buf() {
+            Ok(available) => available,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        if available.is_empty() {
+            return Ok(false);
+        }
+        let available = &available[..available.len().min(limit - buffer.len())];
+        if let Some(end) = available.iter().position(|byte| *byte == b'\n') {
+            buffer.extend_from_slice(&available[..=end]);
+            reader.consume(end + 1);
+            return Ok(false);
+        }
+        let len = available.len();
+        buffer.extend_from_slice(available);
+        reader.consume(len);
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+    use crate::options::Scheme;
+
+    fn convert_stream(converter: &Converter, text: &str) -> Result<String, StreamError> {
+        // Read one byte at a time to split every grapheme and unit
+        let reader = BufReader::with_capacity(1, text.as_bytes());
+        let mut output = Vec::new();
+        converter.convert_stream(reader, &mut output)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn stream() {
+        let texts = [
+            "",
+            "\n",
+            "takūrafi c'y ts' wesimburengge\n\n  cooha  be\r\nacaha",
+            "jugūn\n\n\n",
+            "{zi\nci}",
+            "hafan {Zhang\r\nSanfeng} i {zi}bithe\n",
+        ];
+        for whitespace in [WhitespacePolicy::Collapse, WhitespacePolicy::Preserve] {
+            let converter = Converter::builder().whitespace(whitespace).build();
+            for text in texts {
+                assert_eq!(
+                    convert_stream(&converter, text).unwrap(),
+                    converter.convert(text).unwrap()
+                );
+            }
+        }
+
+        // Each line is read with the scheme detected in it
+        let converter = Converter::builder().scheme(Scheme::Auto).build();
+        let text = "manju gisun\nmongγol kele\nbičig\ntakūrafi\n{zhi\nqi} cooha";
+        assert_eq!(
+            convert_stream(&converter, text).unwrap(),
+            converter.convert(text).unwrap()
+        );
+
+        let error = Converter::new()
+            .convert_stream(&b"manju\n\xff"[..], Vec::new())
+            .unwrap_err();
+        assert!(matches!(error, StreamError::Io(_)));
+    }
+
+    #[test]
+    fn stream_errors() {
+        let converter = Converter::new();
+        let text = "manju\ncooha\r\nacaQa c'a";
+        let mut output = Vec::new();
+        let error = converter
+            .convert_stream(text.as_bytes(), &mut output)
+            .unwrap_err();
+        let StreamError::Conversion(error) = error else {
+            panic!("{}", error)
+        };
+        assert_eq!(error, converter.convert(text).unwrap_err());
+        assert_eq!(String::from_utf8(output).unwrap(), "ᠮᠠᠨᠵᡠ\nᠴᠣᠣᡥᠠ");
+
+        let mut output = Vec::new();
+        let errors = converter
+            .convert_stream_with_diagnostics(text.as_bytes(), &mut output)
+            .unwrap();
+        let result = converter.convert_with_diagnostics(text);
+        assert_eq!(errors, result.errors);
+        assert_eq!(String::from_utf8(output).unwrap(), result.output);
+
+        // A "{" that is never closed is reported at the end of the input
+        let text = "manju\n{zi\nci @ }}\n{cooha";
+        let errors = converter
+            .convert_stream_with_diagnostics(text.as_bytes(), Vec::new())
+            .unwrap();
+        assert_eq!(errors, converter.convert_with_diagnostics(text).errors);
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[test]
+    fn long_lines() {
+        let texts = [
+            "takūrafi c'y  ts' wesimburengge cooha be acaha",
+            "  manju gisun @ }} {zi\u{3000}ci} jugūn \r\nabka na\n\nc'a ",
+            "{hafan  wesimburengge",
+        ];
+        for whitespace in [WhitespacePolicy::Collapse, WhitespacePolicy::Preserve] {
+            let converter = Converter::builder().whitespace(whitespace).build();
+            for text in texts {
+                let result = converter.convert_with_diagnostics(text);
+                // Cut lines in parts of a few bytes, and read on at words longer than that
+                for limit in 1..16 {
+                    let mut output = Vec::new();
+                    let mut errors = Vec::new();
+                    converter
+                        .stream(text.as_bytes(), &mut output, &mut errors, false, limit)
+                        .unwrap();
+                    assert_eq!(String::from_utf8(output).unwrap(), result.output);
+                    assert_eq!(errors, result.errors, "{}", limit);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,403 @@
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::converter::{markup, Converter, LineTables};
+use crate::latin_manchu_unicode_mapper::LatinManchuTable;
+use crate::options::{ErrorPolicy, Scheme, Target};
+
+/// Kind of a [`Token`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// A Latin unit with an entry in the mapping table, such as "a", "ng" or "c'y"
+    Letter,
+    /// Whitespace, copied as it is
+    Whitespace,
+    /// A grapheme that cannot be converted, or a word of a scheme that the target does not write,
+    /// written according to the error policy
+    Unknown,
+    /// "{" or "}" around a Chinese loanword in Hanyu Pinyin, which is not written
+    Markup,
+}
+
+/// A unit of the input and what it is converted to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    /// The matched input, such as "ng" or "c'y"
+    pub unit: &'a str,
+    /// The code points written for the unit, or what the target writes for it
+    pub emitted: String,
+    /// Byte range of the unit in the input
+    pub input: Range<usize>,
+    /// Byte range of the emitted code points in the output
+    pub output: Range<usize>,
+}
+
+/// Iterator over the tokens of a text, created by [`Converter::tokens`]
+///
+/// The output ranges index the concatenation of every emitted text. This is the output of
+/// [`WhitespacePolicy::Preserve`](crate::WhitespacePolicy::Preserve) when no error occurs and no
+/// normalization is applied.
+/// With [`ErrorPolicy::Abort`] graphemes that cannot be converted are emitted as they are.
+///
+/// ## Example
+///
+/// ```rust
+/// use manchu_converter::Converter;
+///
+/// fn main() {
+///     let converter = Converter::new();
+///     let tokens = converter.tokens("wesimburengge").collect::<Vec<_>>();
+///     let ng = tokens.iter().find(|token| token.input.contains(&9)).unwrap();
+///     assert_eq!(ng.unit, "ng");
+///     assert_eq!(ng.emitted, "ᠩ");
+///     assert_eq!(ng.output, 27..30);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tokens<'a> {
+    converter: &'a Converter,
+    tables: LineTables<'a>,
+    /// Table of the line at the position
+    table: &'a LatinManchuTable,
+    loanword_table: &'a LatinManchuTable,
+    /// Scheme of the line at the position
+    scheme: Scheme,
+    /// Whether the position is between "{" and "}"
+    in_loanword: bool,
+    target: Target,
+    text: &'a str,
+    position: usize,
+    output_len: usize,
+    /// Code point of the last letter right before the position
+    previous: Option<char>,
+}
+
+impl<'a> Tokens<'a> {
+    /// Tokens of `text` read with the table of each line, with what `target` writes for them
+    pub(crate) fn new(
+        converter: &'a Converter,
+        text: &'a str,
+        target: Target,
+        tables: LineTables<'a>,
+    ) -> Self {
+        let (scheme, table) = tables.at(0);
+        Tokens {
+            converter,
+            tables,
+            table,
+            scheme,
+            loanword_table: converter.loanword_table(),
+            in_loanword: false,
+            target,
+            text,
+            position: 0,
+            output_len: 0,
+            previous: None,
+        }
+    }
+}
+
+impl<'a> Tokens<'a> {
+    fn table(&self) -> &'a LatinManchuTable {
+        if self.in_loanword {
+            self.loanword_table
+        } else {
+            self.table
+        }
+    }
+
+    /// Whether "{" and "}" are loanword markup
+    fn markup(&self) -> bool {
+        self.scheme.writes_manchu()
+    }
+
+    /// What the error policy writes for a grapheme that cannot be converted
+    fn recovered<'b>(&'b self, grapheme: &'b str) -> &'b str {
+        match self.converter.error_policy() {
+            ErrorPolicy::Abort | ErrorPolicy::PassThrough => grapheme,
+            ErrorPolicy::Replace(marker) => marker,
+            ErrorPolicy::Skip => "",
+        }
+    }
+
+    /// What is written for the letters `unicode` of a unit that ends at `end`
+    fn emit(&self, unicode: &str, end: usize) -> String {
+        if self.target == Target::Script {
+            return unicode.to_string();
+        }
+        // The letters of the unit and the next two code points after it in the word, as the
+        // converted text holds them, which the targets read each letter with
+        let mut letters = unicode.chars().collect::<Vec<_>>();
+        let count = letters.len();
+        let mut position = end;
+        let mut in_loanword = self.in_loanword;
+        while letters.len() < count + 2 {
+            let rest = &self.text[position..];
+            let Some(grapheme) = rest.graphemes(true).next() else {
+                break;
+            };
+            if grapheme.chars().all(char::is_whitespace) {
+                break;
+            }
+            if let Some(open) = markup(rest).filter(|open| self.markup() && *open != in_loanword) {
+                in_loanword = open;
+                position += 1;
+                continue;
+            }
+            let table = if in_loanword {
+                self.loanword_table
+            } else {
+                self.table
+            };
+            match table.longest_match(rest) {
+                Some((len, next)) => {
+                    letters.extend(next.chars());
+                    position += len;
+                }
+                None => {
+                    letters.extend(self.recovered(grapheme).chars());
+                    position += grapheme.len();
+                }
+            }
+        }
+        let mut emitted = String::new();
+        for i in 0..count {
+            let previous = i.checked_sub(1).map_or(self.previous, |i| Some(letters[i]));
+            if !self
+                .target
+                .write(letters[i], previous, &letters[i + 1..], &mut emitted)
+            {
+                emitted.push(letters[i]);
+            }
+        }
+        emitted
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.position;
+        let rest = &self.text[start..];
+        let grapheme = rest.graphemes(true).next()?;
+        let (scheme, table) = self.tables.at(start);
+        self.table = table;
+        self.scheme = scheme;
+        let mut letter = None;
+        let (kind, len, emitted) = if grapheme.chars().all(char::is_whitespace) {
+            (TokenKind::Whitespace, grapheme.len(), grapheme.to_string())
+        } else if self.target != Target::Script && !scheme.writes_manchu() {
+            // The whole word, which the converter does not write either
+            let word = rest.split(char::is_whitespace).next().unwrap_or(rest);
+            (
+                TokenKind::Unknown,
+                word.len(),
+                self.recovered(word).to_string(),
+            )
+        } else if let Some(in_loanword) =
+            markup(rest).filter(|open| self.markup() && *open != self.in_loanword)
+        {
+            self.in_loanword = in_loanword;
+            // Nothing is written for the markup, so the letter before it stays the previous one
+            letter = self.previous;
+            (TokenKind::Markup, 1, String::new())
+        } else {
+            match self.table().longest_match(rest) {
+                Some((len, unicode)) => {
+                    // A unit written as nothing, such as the apostrophe between pinyin syllables,
+                    // leaves the letter before it
+                    letter = unicode.chars().last().or(self.previous);
+                    (TokenKind::Letter, len, self.emit(unicode, start + len))
+                }
+                None => {
+                    let recovered = self.recovered(grapheme);
+                    // The converted text holds what is recovered, if anything, before the next letter
+                    letter = recovered.chars().last().or(self.previous);
+                    (TokenKind::Unknown, grapheme.len(), recovered.to_string())
+                }
+            }
+        };
+        let end = start + len;
+        self.position = end;
+        self.previous = letter;
+        let output = self.output_len..self.output_len + emitted.len();
+        self.output_len = output.end;
+        Some(Token {
+            kind,
+            unit: &self.text[start..end],
+            emitted,
+            input: start..end,
+            output,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::WhitespacePolicy;
+
+    #[test]
+    fn tokens() {
+        let converter = Converter::new();
+        let tokens = converter.tokens("c'y ts'Q").collect::<Vec<_>>();
+        let units = tokens
+            .iter()
+            .map(|token| (token.kind, token.unit, token.emitted.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            units,
+            vec![
+                (TokenKind::Letter, "c'y", "\u{1871}"),
+                (TokenKind::Whitespace, " ", " "),
+                (TokenKind::Letter, "ts'", "\u{186e}"),
+                (TokenKind::Unknown, "Q", "Q"),
+            ]
+        );
+        let ranges = tokens
+            .iter()
+            .map(|token| (token.input.clone(), token.output.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranges,
+            vec![(0..3, 0..3), (3..4, 3..4), (4..7, 4..7), (7..8, 7..8)]
+        );
+
+        let converter = Converter::builder()
+            .error_policy(ErrorPolicy::Skip)
+            .whitespace(WhitespacePolicy::Preserve)
+            .build();
+        let text = "takū\r\nrafiQ";
+        let emitted = converter
+            .tokens(text)
+            .map(|token| token.emitted)
+            .collect::<String>();
+        assert_eq!(emitted, converter.convert(text).unwrap());
+        let token = converter.tokens(text).nth(3).unwrap();
+        assert_eq!((token.unit, token.input, token.output), ("ū", 3..5, 9..12));
+    }
+
+    #[test]
+    fn ipa_tokens() {
+        let converter = Converter::builder().target(Target::Ipa).build();
+        let text = "kaki si";
+        let tokens = converter
+            .tokens(text)
+            .map(|token| (token.unit, token.emitted, token.output))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                ("k", "qʰ".to_string(), 0..3),
+                ("a", "a".to_string(), 3..4),
+                ("k", "kʰ".to_string(), 4..7),
+                ("i", "i".to_string(), 7..8),
+                (" ", " ".to_string(), 8..9),
+                ("s", "ɕ".to_string(), 9..11),
+                ("i", "i".to_string(), 11..12),
+            ]
+        );
+        let emitted = converter
+            .tokens(text)
+            .map(|token| token.emitted)
+            .collect::<String>();
+        assert_eq!(emitted, converter.convert(text).unwrap());
+    }
+
+    #[test]
+    fn target_tokens() {
+        let converter = Converter::builder().target(Target::Katakana).build();
+        let text = "takūrafi abka";
+        let tokens = converter
+            .tokens(text)
+            .map(|token| token.emitted)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec!["タ", "", "ク", "ー", "ラ", "", "フィ", "", " ", "ア", "ブ", "カ", ""]
+        );
+        assert_eq!(tokens.concat(), converter.convert(text).unwrap());
+
+        let converter = Converter::builder().target(Target::Hangul).build();
+        let emitted = converter
+            .tokens("abka manju")
+            .map(|token| token.emitted)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            emitted,
+            vec!["압", "", "", "카", " ", "", "만", "", "", "주"]
+        );
+
+        // The letters after a unit are read as the error policy leaves them
+        for error_policy in [
+            ErrorPolicy::Skip,
+            ErrorPolicy::PassThrough,
+            ErrorPolicy::Replace(String::new()),
+        ] {
+            let converter = Converter::builder()
+                .target(Target::Ipa)
+                .error_policy(error_policy)
+                .build();
+            for text in ["kQa", "k{a}", "kQ{a}", "bak}a"] {
+                let emitted = converter
+                    .tokens(text)
+                    .map(|token| token.emitted)
+                    .collect::<String>();
+                assert_eq!(emitted, converter.convert(text).unwrap(), "{}", text);
+            }
+        }
+        // Loanword markup between two letters leaves them next to each other
+        for target in [Target::Ipa, Target::Katakana, Target::Hangul, Target::Sibe] {
+            let converter = Converter::builder().target(target).build();
+            for text in [
+                "k{a}",
+                "mjh{uh}",
+                "{zhong}guo",
+                "abka{an}",
+                "{g'u}",
+                "{xi'an}",
+            ] {
+                let emitted = converter
+                    .tokens(text)
+                    .map(|token| token.emitted)
+                    .collect::<String>();
+                assert_eq!(emitted, converter.convert(text).unwrap(), "{}", text);
+            }
+        }
+        let converter = Converter::builder().target(Target::Hangul).build();
+        assert_eq!(converter.convert("k{a}").unwrap(), "카");
+
+        // The letter after a grapheme that cannot be converted follows what is recovered for it
+        for error_policy in [
+            ErrorPolicy::Skip,
+            ErrorPolicy::PassThrough,
+            ErrorPolicy::Replace(String::new()),
+            ErrorPolicy::replacement_character(),
+        ] {
+            for target in [Target::Ipa, Target::Katakana, Target::Hangul, Target::Sibe] {
+                let converter = Converter::builder()
+                    .target(target)
+                    .error_policy(error_policy.clone())
+                    .whitespace(WhitespacePolicy::Preserve)
+                    .build();
+                for text in ["geocz'o", "kQa", "aQng", "mQi abQka", "nQo\nQgi"] {
+                    let emitted = converter
+                        .tokens(text)
+                        .map(|token| token.emitted)
+                        .collect::<String>();
+                    assert_eq!(emitted, converter.convert(text).unwrap(), "{}", text);
+                }
+            }
+        }
+
+        let converter = Converter::builder()
+            .target(Target::Ipa)
+            .error_policy(ErrorPolicy::Skip)
+            .build();
+        let tokens = converter.tokens("kQa").collect::<Vec<_>>();
+        assert_eq!(tokens[0].emitted, "qʰ");
+    }
+}